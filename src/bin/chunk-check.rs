@@ -0,0 +1,104 @@
+//! Checks the chunks of `assets/data/chunks.ron` without starting the game.
+//!
+//! Usage: `cargo run --bin chunk-check [chunks.ron] [platforms.ron]`
+
+use std::process::exit;
+
+use ron::de::from_bytes;
+
+use bevy_jam_1::cheat_codes::CheatCodeResource;
+use bevy_jam_1::platforms::{
+    chunk::{Chunk, ChunksResource},
+    platform::PlatformKindsResource,
+    reachability,
+};
+
+fn read_file(path: &str) -> Vec<u8> {
+    match std::fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(error) => {
+            eprintln!("Cannot read {}: {}", path, error);
+            exit(2);
+        }
+    }
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    let chunks_path = args
+        .get(1)
+        .map_or("assets/data/chunks.ron", |arg| arg.as_str());
+    let platforms_path = args
+        .get(2)
+        .map_or("assets/data/platforms.ron", |arg| arg.as_str());
+
+    let chunks_resource = match from_bytes::<ChunksResource>(&read_file(chunks_path)) {
+        Ok(chunks_resource) => chunks_resource,
+        Err(error) => {
+            eprintln!("{}: {}", chunks_path, error);
+            exit(1);
+        }
+    };
+    let platform_kinds = match from_bytes::<PlatformKindsResource>(&read_file(platforms_path)) {
+        Ok(platform_kinds) => platform_kinds,
+        Err(error) => {
+            eprintln!("{}: {}", platforms_path, error);
+            exit(1);
+        }
+    };
+    let cheat_codes = CheatCodeResource::new();
+
+    let named_chunks: Vec<(String, &Chunk)> = chunks_resource
+        .prelude_chunks
+        .iter()
+        .enumerate()
+        .map(|(i, chunk)| (format!("prelude_chunks[{}]", i), chunk))
+        .chain(
+            chunks_resource
+                .chunks
+                .iter()
+                .enumerate()
+                .map(|(i, chunk)| (format!("chunks[{}]", i), chunk)),
+        )
+        .collect();
+
+    let mut error_count = 0;
+
+    for (name, chunk) in named_chunks.iter() {
+        for error in reachability::check_chunk(chunk, &platform_kinds, &cheat_codes) {
+            println!("{}: {}", name, error);
+            error_count += 1;
+        }
+
+        // the prelude chunks are spawned at the origin, without their offset
+        let spawned_chunk = if name.starts_with("prelude") {
+            Chunk {
+                chunk_offset: 0.0,
+                ..(*chunk).clone()
+            }
+        } else {
+            (*chunk).clone()
+        };
+
+        // any chunk can be followed by any of the random chunks
+        for (i, next_chunk) in chunks_resource.chunks.iter().enumerate() {
+            if let Some(error) =
+                reachability::check_transition(&spawned_chunk, next_chunk, &platform_kinds)
+            {
+                println!("{} -> chunks[{}]: {}", name, i, error);
+                error_count += 1;
+            }
+        }
+    }
+
+    if error_count > 0 {
+        println!(
+            "{} error(s) found in {} chunks",
+            error_count,
+            named_chunks.len()
+        );
+        exit(1);
+    }
+
+    println!("{} chunks checked, no error found", named_chunks.len());
+}
@@ -0,0 +1,189 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy_kira_audio::Audio;
+use ron::de::from_bytes;
+use serde::Deserialize;
+
+use crate::platforms::data::{self, DataResource};
+use crate::platforms::platform::PlatformKind;
+use crate::states::GameStates;
+use crate::stats::GameStatsResource;
+use crate::toast::ShowToast;
+
+// length of the fade between two biomes, in seconds
+const TRANSITION_DURATION: f32 = 1.5;
+
+pub struct BiomesPlugin;
+
+impl Plugin for BiomesPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(
+            from_bytes::<BiomesResource>(include_bytes!("../assets/data/biomes.ron")).unwrap(),
+        )
+        .insert_resource(CurrentBiome { index: None })
+        .add_startup_system(data::load_data::<BiomesResource>)
+        .add_system(data::reload_data::<BiomesResource>)
+        .add_system_set(SystemSet::on_enter(GameStates::Main).with_system(reset_current_biome))
+        .add_system_set(
+            SystemSet::on_update(GameStates::Main)
+                .with_system(update_current_biome)
+                .with_system(fade_transition),
+        )
+        .add_system_set(SystemSet::on_exit(GameStates::Main).with_system(despawn_transitions));
+    }
+}
+
+#[derive(Deserialize)]
+pub struct BackgroundData {
+    pub sprite: String,
+    // in pixels
+    pub size: Vec2,
+    pub scale: Vec2,
+}
+
+impl BackgroundData {
+    /// Width of a background layer once scaled
+    pub fn width(&self) -> f32 {
+        self.size.x * self.scale.x
+    }
+}
+
+#[derive(Deserialize)]
+pub struct BiomeData {
+    pub name: String,
+    // one of them is picked for every background layer
+    pub backgrounds: Vec<BackgroundData>,
+    pub music: String,
+    // applied to the platforms and the backgrounds
+    #[serde(default = "default_tint")]
+    pub tint: (f32, f32, f32),
+    // replaces the sprite of the platform kinds
+    #[serde(default)]
+    pub platform_sprites: HashMap<PlatformKind, String>,
+}
+
+fn default_tint() -> (f32, f32, f32) {
+    (1.0, 1.0, 1.0)
+}
+
+impl BiomeData {
+    pub fn tint_color(&self) -> Color {
+        Color::rgb(self.tint.0, self.tint.1, self.tint.2)
+    }
+}
+
+/// Biomes defined in `assets/data/biomes.ron`, following each other every `biome_length` meters
+#[derive(Deserialize)]
+pub struct BiomesResource {
+    pub biome_length: f32,
+    pub biomes: Vec<BiomeData>,
+}
+
+impl DataResource for BiomesResource {
+    const PATH: &'static str = "data/biomes.ron";
+}
+
+impl BiomesResource {
+    pub fn index_at(&self, distance: f32) -> Option<usize> {
+        if self.biomes.is_empty() || self.biome_length <= 0.0 {
+            return None;
+        }
+
+        Some((distance.max(0.0) / self.biome_length) as usize % self.biomes.len())
+    }
+
+    /// Biome at a distance in meters
+    pub fn at(&self, distance: f32) -> Option<&BiomeData> {
+        self.index_at(distance).map(|index| &self.biomes[index])
+    }
+}
+
+/// Biome the player is in
+pub struct CurrentBiome {
+    pub index: Option<usize>,
+}
+
+#[derive(Component)]
+pub struct BiomeTransition(Timer);
+
+fn reset_current_biome(mut current_biome: ResMut<CurrentBiome>) {
+    current_biome.index = None;
+}
+
+fn update_current_biome(
+    mut commands: Commands,
+    stats: Res<GameStatsResource>,
+    biomes: Res<BiomesResource>,
+    mut current_biome: ResMut<CurrentBiome>,
+    asset_server: Res<AssetServer>,
+    audio: Res<Audio>,
+    mut toast_writer: EventWriter<ShowToast>,
+) {
+    let index = match biomes.index_at(stats.distance) {
+        Some(index) => index,
+        None => return,
+    };
+    if current_biome.index == Some(index) {
+        return;
+    }
+
+    let is_first_biome = current_biome.index.is_none();
+    current_biome.index = Some(index);
+
+    let biome = &biomes.biomes[index];
+    info!("[BiomesPlugin] Entering {}", biome.name);
+
+    audio.stop();
+    audio.play_looped(asset_server.load(biome.music.as_str()));
+
+    // no transition at the start of the run
+    if is_first_biome {
+        return;
+    }
+
+    toast_writer.send(ShowToast {
+        value: format!("Entering: {}", biome.name),
+        duration: Duration::from_secs(3),
+    });
+
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                ..Default::default()
+            },
+            color: Color::BLACK.into(),
+            ..Default::default()
+        })
+        .insert(BiomeTransition(Timer::from_seconds(
+            TRANSITION_DURATION,
+            false,
+        )));
+}
+
+fn fade_transition(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut transition_query: Query<(Entity, &mut BiomeTransition, &mut UiColor)>,
+) {
+    for (entity, mut transition, mut color) in transition_query.iter_mut() {
+        transition.0.tick(time.delta());
+        color.0.set_a(1.0 - transition.0.percent());
+
+        if transition.0.finished() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+fn despawn_transitions(
+    mut commands: Commands,
+    transition_query: Query<Entity, With<BiomeTransition>>,
+) {
+    for entity in transition_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
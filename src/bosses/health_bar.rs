@@ -0,0 +1,93 @@
+use bevy::prelude::*;
+
+use super::{Boss, BossFight};
+use crate::enemies::Enemy;
+
+// in pixels
+const BAR_WIDTH: f32 = 500.0;
+const BAR_HEIGHT: f32 = 16.0;
+
+#[derive(Component)]
+pub struct BossHealthBar;
+
+#[derive(Component)]
+pub struct BossHealthFill;
+
+pub fn build_ui(commands: &mut Commands, asset_server: &AssetServer, name: &str) {
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    top: Val::Px(10.),
+                    left: Val::Percent(50.),
+                    ..Default::default()
+                },
+                margin: Rect {
+                    left: Val::Px(-BAR_WIDTH / 2.),
+                    ..Default::default()
+                },
+                flex_direction: FlexDirection::ColumnReverse,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            color: Color::rgba_u8(0, 0, 0, 0).into(),
+            ..Default::default()
+        })
+        .insert(BossHealthBar)
+        .with_children(|parent| {
+            parent.spawn_bundle(TextBundle {
+                text: Text::with_section(
+                    name,
+                    TextStyle {
+                        font: asset_server.load("fonts/SpaceMadness.ttf"),
+                        font_size: 24.,
+                        color: Color::rgb_u8(255, 255, 255).into(),
+                    },
+                    TextAlignment {
+                        ..Default::default()
+                    },
+                ),
+                ..Default::default()
+            });
+            parent
+                .spawn_bundle(NodeBundle {
+                    style: Style {
+                        size: Size::new(Val::Px(BAR_WIDTH), Val::Px(BAR_HEIGHT)),
+                        ..Default::default()
+                    },
+                    color: Color::rgb_u8(40, 10, 10).into(),
+                    ..Default::default()
+                })
+                .with_children(|parent| {
+                    parent
+                        .spawn_bundle(NodeBundle {
+                            style: Style {
+                                size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                                ..Default::default()
+                            },
+                            color: Color::rgb_u8(200, 30, 30).into(),
+                            ..Default::default()
+                        })
+                        .insert(BossHealthFill);
+                });
+        });
+}
+
+pub fn update_health_bar(
+    boss_fight: Res<BossFight>,
+    boss_query: Query<&Enemy, With<Boss>>,
+    mut fill_query: Query<&mut Style, With<BossHealthFill>>,
+) {
+    let active = match boss_fight.active.as_ref() {
+        Some(active) => active,
+        None => return,
+    };
+
+    if let Ok(enemy) = boss_query.get(active.entity) {
+        let ratio = enemy.health as f32 / active.max_health.max(1) as f32;
+        for mut style in fill_query.iter_mut() {
+            style.size.width = Val::Percent(ratio * 100.);
+        }
+    }
+}
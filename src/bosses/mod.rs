@@ -0,0 +1,560 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+use rand::seq::SliceRandom;
+use ron::de::from_bytes;
+use serde::Deserialize;
+
+use crate::biomes::BiomesResource;
+use crate::camera::CameraLock;
+use crate::cheat_codes::{CheatCodeKind, CheatCodeRarity, CheatCodeResource};
+use crate::enemies::{
+    self, Enemy, EnemyAi, EnemyAiData, EnemyKind, EnemyKindsResource, EnemyState,
+};
+use crate::interactables::spawn_char;
+use crate::letter_director::LetterDirector;
+use crate::platforms::chunk::{spawn_chunk, Chunk, ChunksResource};
+use crate::platforms::data::{self, DataResource};
+use crate::platforms::platform::PlatformKindsResource;
+use crate::runner::{CodeFragment, Player, RunSeed};
+use crate::states::GameStates;
+use crate::stats::BossDefeatedEvent;
+use crate::toast::ShowToast;
+
+mod health_bar;
+
+// how far into the arena the player walks before the fight starts, in pixels
+const ENGAGE_MARGIN: f32 = 150.0;
+// walls closing the arena during the fight, in pixels
+const WALL_HALF_WIDTH: f32 = 10.0;
+const WALL_HALF_HEIGHT: f32 = 1000.0;
+// the boss turns around this close to the edges of the arena, in pixels
+const EDGE_MARGIN: f32 = 100.0;
+// angle between two projectiles of a volley, in radians
+const SPREAD_ANGLE: f32 = 0.25;
+// letters dropped by a defeated boss, in pixels
+const LOOT_SPACING: f32 = 40.0;
+const LOOT_HEIGHT: f32 = 40.0;
+
+pub struct BossesPlugin;
+
+impl Plugin for BossesPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(
+            from_bytes::<BossesResource>(include_bytes!("../../assets/data/bosses.ron")).unwrap(),
+        )
+        .insert_resource(BossFight {
+            next_milestone: 0.0,
+            boss_count: 0,
+            active: None,
+        })
+        .add_startup_system(data::load_data::<BossesResource>)
+        .add_system(data::reload_data::<BossesResource>)
+        .add_system_set(SystemSet::on_enter(GameStates::Main).with_system(reset_boss_fight))
+        .add_system_set(
+            SystemSet::on_update(GameStates::Main)
+                .with_system(engage_boss.label("engage_boss"))
+                .with_system(boss_behavior.after("engage_boss"))
+                .with_system(detect_boss_defeat.label("boss_defeat").after("engage_boss"))
+                // the new boss only exists once the commands are applied
+                .with_system(spawn_boss_chunks.after("boss_defeat"))
+                .with_system(health_bar::update_health_bar.after("engage_boss")),
+        )
+        .add_system_set(SystemSet::on_exit(GameStates::Main).with_system(despawn_bosses));
+    }
+}
+
+/// Part of a boss fight, until the boss lost the health of the phase
+#[derive(Deserialize, Debug, Clone)]
+pub struct BossPhase {
+    pub health: u32,
+    // in pixels per second
+    pub speed: f32,
+    // in seconds
+    pub fire_interval: f32,
+    // in pixels per second
+    pub projectile_speed: f32,
+    // projectiles of a volley
+    pub shots: u32,
+}
+
+#[derive(Deserialize)]
+pub struct BossData {
+    pub name: String,
+    pub sprite: String,
+    #[serde(default = "default_tint")]
+    pub tint: (f32, f32, f32),
+    pub sprite_scale: f32,
+    // radius of the collider, in pixels
+    pub radius: f32,
+    pub score: usize,
+    // in seconds
+    pub stun_time: f32,
+    // relative to the arena chunk, in pixels
+    pub position: Vec2,
+    // left and right edges of the arena, relative to the arena chunk, in pixels
+    pub arena: (f32, f32),
+    pub phases: Vec<BossPhase>,
+    pub chunk: Chunk,
+}
+
+fn default_tint() -> (f32, f32, f32) {
+    (1.0, 1.0, 1.0)
+}
+
+/// Bosses defined in `assets/data/bosses.ron`, one of them every `interval` meters
+#[derive(Deserialize)]
+pub struct BossesResource {
+    pub interval: f32,
+    pub bosses: Vec<BossData>,
+}
+
+impl DataResource for BossesResource {
+    const PATH: &'static str = "data/bosses.ron";
+}
+
+/// Boss of the current arena
+pub struct ActiveBoss {
+    pub entity: Entity,
+    pub name: String,
+    pub max_health: u32,
+    // in pixels
+    arena: (f32, f32),
+    last_position: Vec2,
+    pub is_engaged: bool,
+}
+
+pub struct BossFight {
+    // in meters
+    next_milestone: f32,
+    // bosses met during the run
+    boss_count: usize,
+    pub active: Option<ActiveBoss>,
+}
+
+#[derive(Debug, Component)]
+pub struct Boss {
+    phases: Vec<BossPhase>,
+    phase: usize,
+    // in pixels
+    arena: (f32, f32),
+    // 1.0 to the right, -1.0 to the left
+    direction: f32,
+    fire_timer: Timer,
+}
+
+impl Boss {
+    fn phase_at(&self, health: u32) -> usize {
+        // the health of the later phases is lost last
+        let mut later_health: u32 = self.phases.iter().map(|phase| phase.health).sum();
+        for (index, phase) in self.phases.iter().enumerate() {
+            later_health = later_health.saturating_sub(phase.health);
+            if health > later_health {
+                return index;
+            }
+        }
+        self.phases.len() - 1
+    }
+}
+
+#[derive(Component)]
+pub struct ArenaWall;
+
+/// Letters dropped by a defeated boss
+#[derive(Component)]
+pub struct BossLoot;
+
+fn reset_boss_fight(
+    mut boss_fight: ResMut<BossFight>,
+    mut camera_lock: ResMut<CameraLock>,
+    bosses: Res<BossesResource>,
+) {
+    boss_fight.next_milestone = bosses.interval;
+    boss_fight.boss_count = 0;
+    boss_fight.active = None;
+    camera_lock.x = None;
+}
+
+fn spawn_boss(
+    boss_data: &BossData,
+    position: Vec2,
+    arena: (f32, f32),
+    commands: &mut Commands,
+    rapier_config: &RapierConfiguration,
+    asset_server: &AssetServer,
+) -> Entity {
+    let max_health = boss_data.phases.iter().map(|phase| phase.health).sum();
+    let ai_data = EnemyAiData {
+        sight_range: arena.1 - arena.0,
+        attack_range: arena.1 - arena.0,
+        needs_line_of_sight: false,
+        alert_time: 0.0,
+        stun_time: boss_data.stun_time,
+    };
+
+    commands
+        .spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: Color::rgb(boss_data.tint.0, boss_data.tint.1, boss_data.tint.2),
+                ..Default::default()
+            },
+            texture: asset_server.load(boss_data.sprite.as_str()),
+            transform: Transform {
+                translation: Vec3::new(0.0, 0.0, 51.0),
+                scale: Vec3::new(boss_data.sprite_scale, boss_data.sprite_scale, 1.0),
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .insert_bundle(RigidBodyBundle {
+            body_type: RigidBodyType::Dynamic.into(),
+            position: (position / rapier_config.scale).into(),
+            mass_properties: RigidBodyMassPropsFlags::ROTATION_LOCKED.into(),
+            ..Default::default()
+        })
+        .insert_bundle(ColliderBundle {
+            shape: ColliderShape::ball(boss_data.radius / rapier_config.scale).into(),
+            material: ColliderMaterial {
+                friction: 0.5,
+                restitution: 0.0,
+                ..Default::default()
+            }
+            .into(),
+            ..Default::default()
+        })
+        .insert(ColliderPositionSync::Discrete)
+        .insert(Enemy {
+            kind: EnemyKind(boss_data.name.clone()),
+            health: max_health,
+            score: boss_data.score,
+        })
+        .insert(EnemyAi::new(ai_data, EnemyState::Idle))
+        .insert(Boss {
+            phases: boss_data.phases.clone(),
+            phase: 0,
+            arena,
+            direction: -1.0,
+            fire_timer: Timer::from_seconds(boss_data.phases[0].fire_interval, true),
+        })
+        .insert(Name::new(format!("Boss-{}", boss_data.name)))
+        .id()
+}
+
+/// Spawns the arena of the next boss once the chunks reach its milestone
+fn spawn_boss_chunks(
+    mut commands: Commands,
+    rapier_config: Res<RapierConfiguration>,
+    asset_server: Res<AssetServer>,
+    mut chunks_resource: ResMut<ChunksResource>,
+    mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+    cheat_codes: Res<CheatCodeResource>,
+    platform_kinds: Res<PlatformKindsResource>,
+    enemy_kinds: Res<EnemyKindsResource>,
+    biomes: Res<BiomesResource>,
+    mut letter_director: ResMut<LetterDirector>,
+    mut run_seed: ResMut<RunSeed>,
+    bosses: Res<BossesResource>,
+    mut boss_fight: ResMut<BossFight>,
+) {
+    if boss_fight.active.is_some()
+        || bosses.bosses.is_empty()
+        || bosses.interval <= 0.0
+        || chunks_resource.furthest_x / 100. < boss_fight.next_milestone
+    {
+        return;
+    }
+
+    let boss_data = &bosses.bosses[boss_fight.boss_count % bosses.bosses.len()];
+    // postponed until the player can fight back
+    if boss_data.phases.is_empty() || !boss_data.chunk.can_be_cleared(&cheat_codes) {
+        return;
+    }
+
+    let x_offset = chunks_resource.furthest_x + boss_data.chunk.chunk_offset;
+    spawn_chunk(
+        &boss_data.chunk,
+        x_offset,
+        &mut commands,
+        &rapier_config,
+        &asset_server,
+        &mut texture_atlases,
+        &cheat_codes,
+        &platform_kinds,
+        &enemy_kinds,
+        1.0,
+        1.0,
+        &mut letter_director,
+        &mut run_seed,
+        biomes.at(chunks_resource.furthest_x / 100.),
+    );
+    chunks_resource.furthest_x += boss_data.chunk.next_chunk_offset;
+    chunks_resource.last_chunk = Some(boss_data.chunk.clone());
+
+    let arena = (x_offset + boss_data.arena.0, x_offset + boss_data.arena.1);
+    let position = boss_data.position + Vec2::new(x_offset, 0.0);
+    let entity = spawn_boss(
+        boss_data,
+        position,
+        arena,
+        &mut commands,
+        &rapier_config,
+        &asset_server,
+    );
+    info!(
+        "[BossesPlugin] Spawned {} at {}",
+        boss_data.name, position.x
+    );
+
+    boss_fight.active = Some(ActiveBoss {
+        entity,
+        name: boss_data.name.clone(),
+        max_health: boss_data.phases.iter().map(|phase| phase.health).sum(),
+        arena,
+        last_position: position,
+        is_engaged: false,
+    });
+    boss_fight.boss_count += 1;
+    boss_fight.next_milestone += bosses.interval;
+}
+
+fn spawn_wall(x: f32, commands: &mut Commands, rapier_config: &RapierConfiguration) {
+    commands
+        .spawn_bundle(RigidBodyBundle {
+            body_type: RigidBodyType::Static.into(),
+            position: (Vec2::new(x, 0.0) / rapier_config.scale).into(),
+            ..Default::default()
+        })
+        .insert_bundle(ColliderBundle {
+            shape: ColliderShape::cuboid(
+                WALL_HALF_WIDTH / rapier_config.scale,
+                WALL_HALF_HEIGHT / rapier_config.scale,
+            )
+            .into(),
+            ..Default::default()
+        })
+        .insert(ArenaWall)
+        .insert(Name::new("ArenaWall"));
+}
+
+/// Closes the arena and locks the camera while the player is in it
+fn engage_boss(
+    mut commands: Commands,
+    rapier_config: Res<RapierConfiguration>,
+    asset_server: Res<AssetServer>,
+    mut boss_fight: ResMut<BossFight>,
+    mut camera_lock: ResMut<CameraLock>,
+    player_query: Query<&Transform, With<Player>>,
+    wall_query: Query<Entity, With<ArenaWall>>,
+    health_bar_query: Query<Entity, With<health_bar::BossHealthBar>>,
+    mut toast_writer: EventWriter<ShowToast>,
+) {
+    let boss = match boss_fight.active.as_mut() {
+        Some(boss) => boss,
+        None => return,
+    };
+    let player_x = match player_query.iter().next() {
+        Some(transform) => transform.translation.x,
+        None => return,
+    };
+
+    // a player respawned behind the arena walks back into it
+    let is_in_arena = player_x >= boss.arena.0 && player_x <= boss.arena.1;
+    if !boss.is_engaged && is_in_arena && player_x >= boss.arena.0 + ENGAGE_MARGIN {
+        boss.is_engaged = true;
+        spawn_wall(
+            boss.arena.0 - WALL_HALF_WIDTH,
+            &mut commands,
+            &rapier_config,
+        );
+        spawn_wall(
+            boss.arena.1 + WALL_HALF_WIDTH,
+            &mut commands,
+            &rapier_config,
+        );
+        camera_lock.x = Some((boss.arena.0 + boss.arena.1) / 2.0);
+        health_bar::build_ui(&mut commands, &asset_server, &boss.name);
+
+        toast_writer.send(ShowToast {
+            value: format!("{} blocks the way!", boss.name),
+            duration: Duration::from_secs(3),
+        });
+    } else if boss.is_engaged && !is_in_arena {
+        boss.is_engaged = false;
+        camera_lock.x = None;
+        for entity in wall_query.iter().chain(health_bar_query.iter()) {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+fn boss_behavior(
+    mut commands: Commands,
+    time: Res<Time>,
+    rapier_config: Res<RapierConfiguration>,
+    mut boss_fight: ResMut<BossFight>,
+    mut boss_query: Query<(
+        &mut Boss,
+        &Enemy,
+        &EnemyAi,
+        &Transform,
+        &mut RigidBodyVelocityComponent,
+    )>,
+    mut toast_writer: EventWriter<ShowToast>,
+) {
+    let active = match boss_fight.active.as_mut() {
+        Some(active) => active,
+        None => return,
+    };
+
+    for (mut boss, enemy, ai, transform, mut rb_vel) in boss_query.iter_mut() {
+        let position = transform.translation.truncate();
+        active.last_position = position;
+
+        let phase_index = boss.phase_at(enemy.health);
+        if phase_index != boss.phase {
+            boss.phase = phase_index;
+            boss.fire_timer = Timer::from_seconds(boss.phases[phase_index].fire_interval, true);
+            toast_writer.send(ShowToast {
+                value: format!("{} is getting angry!", active.name),
+                duration: Duration::from_secs(2),
+            });
+        }
+        let phase = boss.phases[phase_index].clone();
+
+        if matches!(ai.state, EnemyState::Stunned | EnemyState::Dead) {
+            rb_vel.linvel.x = 0.0;
+            continue;
+        }
+
+        // paces from one side of the arena to the other
+        let (left, right) = boss.arena;
+        if (boss.direction < 0.0 && position.x <= left + EDGE_MARGIN)
+            || (boss.direction > 0.0 && position.x >= right - EDGE_MARGIN)
+        {
+            boss.direction = -boss.direction;
+        }
+        rb_vel.linvel.x = boss.direction * phase.speed / rapier_config.scale;
+
+        if !active.is_engaged {
+            continue;
+        }
+
+        boss.fire_timer.tick(time.delta());
+        if boss.fire_timer.just_finished() {
+            let aim = ai.to_player.normalize_or_zero();
+            for shot in 0..phase.shots {
+                // fanned out around the player
+                let angle = (shot as f32 - (phase.shots - 1) as f32 / 2.0) * SPREAD_ANGLE;
+                enemies::spawn_projectile(
+                    position,
+                    Mat2::from_angle(angle) * aim * phase.projectile_speed,
+                    &mut commands,
+                    &rapier_config,
+                );
+            }
+        }
+    }
+}
+
+/// Rare code dropped by the bosses, another one once all of them are activated
+fn pick_reward(cheat_codes: &CheatCodeResource) -> Option<CheatCodeKind> {
+    let mut rng = rand::thread_rng();
+    let codes_left = cheat_codes
+        .codes
+        .values()
+        .filter(|code| {
+            code.rarity != CheatCodeRarity::Mandatory && !cheat_codes.is_code_activated(&code.kind)
+        })
+        .collect::<Vec<_>>();
+
+    let rare_codes = codes_left
+        .iter()
+        .filter(|code| code.rarity == CheatCodeRarity::Rare)
+        .collect::<Vec<_>>();
+
+    match rare_codes.choose(&mut rng) {
+        Some(code) => Some(code.kind),
+        None => codes_left.choose(&mut rng).map(|code| code.kind),
+    }
+}
+
+fn detect_boss_defeat(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+    cheat_codes: Res<CheatCodeResource>,
+    mut run_seed: ResMut<RunSeed>,
+    mut boss_fight: ResMut<BossFight>,
+    mut camera_lock: ResMut<CameraLock>,
+    boss_query: Query<(), With<Boss>>,
+    wall_query: Query<Entity, With<ArenaWall>>,
+    health_bar_query: Query<Entity, With<health_bar::BossHealthBar>>,
+    mut boss_defeated_event: EventWriter<BossDefeatedEvent>,
+    mut toast_writer: EventWriter<ShowToast>,
+) {
+    let is_defeated = match boss_fight.active.as_ref() {
+        Some(active) => boss_query.get(active.entity).is_err(),
+        None => false,
+    };
+    if !is_defeated {
+        return;
+    }
+
+    let active = boss_fight.active.take().unwrap();
+    info!("[BossesPlugin] {} defeated", active.name);
+    boss_defeated_event.send(BossDefeatedEvent);
+
+    camera_lock.x = None;
+    for entity in wall_query.iter().chain(health_bar_query.iter()) {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    let reward = pick_reward(&cheat_codes).and_then(|kind| cheat_codes.codes.get(&kind));
+    if let Some(code) = reward {
+        let order = run_seed.permutation(code.text.len());
+        let width = (order.len() as f32 - 1.0) * LOOT_SPACING;
+        for (n, index) in order.into_iter().enumerate() {
+            let position = active.last_position
+                + Vec2::new(n as f32 * LOOT_SPACING - width / 2.0, LOOT_HEIGHT);
+            let ch_entity = spawn_char(
+                &mut commands,
+                &asset_server,
+                &mut texture_atlases,
+                code.text.chars().nth(index).unwrap(),
+                &position,
+            );
+            commands
+                .entity(ch_entity)
+                .insert(CodeFragment {
+                    kind: code.kind,
+                    index,
+                })
+                .insert(BossLoot);
+        }
+    }
+
+    toast_writer.send(ShowToast {
+        value: format!("{} defeated!", active.name),
+        duration: Duration::from_secs(3),
+    });
+}
+
+fn despawn_bosses(
+    mut commands: Commands,
+    mut camera_lock: ResMut<CameraLock>,
+    query: Query<
+        Entity,
+        Or<(
+            With<Boss>,
+            With<ArenaWall>,
+            With<BossLoot>,
+            With<health_bar::BossHealthBar>,
+        )>,
+    >,
+) {
+    camera_lock.x = None;
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -6,6 +6,12 @@ pub struct UICameraComponent;
 #[derive(Component)]
 pub struct TwoDCameraComponent;
 
+/// Where the camera stays instead of following the player, in pixels
+#[derive(Default)]
+pub struct CameraLock {
+    pub x: Option<f32>,
+}
+
 pub fn add_camera(mut commands: Commands) {
     info!("Spawning cameras");
     commands
@@ -1,9 +1,9 @@
 use rand::distributions::{Alphanumeric, DistString};
 use rand::prelude::SliceRandom;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug, Deserialize)]
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug, Deserialize, Serialize)]
 pub enum CheatCodeKind {
     // Mandatory
     Jump,
@@ -96,10 +96,17 @@ impl CheatCode {
 pub struct CheatCodeResource {
     pub codes: HashMap<CheatCodeKind, CheatCode>,
     activated: Vec<CheatCodeKind>,
+    // rolled once, shared by the letter director and the terminals
+    next_code: Option<CheatCodeKind>,
 }
 
 impl CheatCodeResource {
-    pub fn get_next_code(&self) -> CheatCodeKind {
+    /// Code the player should go for next, none once every code is activated
+    pub fn get_next_code(&self) -> Option<CheatCodeKind> {
+        self.next_code
+    }
+
+    fn roll_next_code(&self) -> Option<CheatCodeKind> {
         // first get a list of mandatory cheat codes (JUMP)
         let mandatories = self
             .codes
@@ -112,7 +119,7 @@ impl CheatCodeResource {
             .collect::<Vec<CheatCodeKind>>();
         // if there is a mandatory code to be chosen, then return it
         if !mandatories.is_empty() {
-            return *mandatories.choose(&mut rand::thread_rng()).unwrap();
+            return mandatories.choose(&mut rand::thread_rng()).copied();
         }
 
         // then we grab all the codes that haven't been activated yet
@@ -138,31 +145,46 @@ impl CheatCodeResource {
 
         available_codes
             .choose_weighted(&mut rand::thread_rng(), |code| code.rarity as u8)
-            .unwrap()
-            .kind
+            .ok()
+            .map(|code| code.kind)
     }
 
     pub fn activate_code(&mut self, text: &str) -> CheatCodeActivationResult {
-        // iteration over all the existing codes
-        for (_, code) in self.codes.iter() {
-            // it we found a code
-            if code.text.eq(&text.to_lowercase()) {
-                if self.is_code_activated(&code.kind) {
-                    return CheatCodeActivationResult::AlreadyActivated(code.kind);
-                }
-
-                // if the code hasn't been activated do it
-                self.activated.push(code.kind);
-                return CheatCodeActivationResult::Activated(code.kind);
-            }
+        // search over all the existing codes
+        let kind = match self
+            .codes
+            .values()
+            .find(|code| code.text.eq(&text.to_lowercase()))
+        {
+            Some(code) => code.kind,
+            None => return CheatCodeActivationResult::NotFound,
+        };
+
+        if self.is_code_activated(&kind) {
+            return CheatCodeActivationResult::AlreadyActivated(kind);
         }
-        CheatCodeActivationResult::NotFound
+
+        // if the code hasn't been activated do it
+        self.activated.push(kind);
+        self.refresh_next_code();
+        CheatCodeActivationResult::Activated(kind)
     }
 
     pub fn deactivate_code(&mut self, kind: &CheatCodeKind) {
         if self.is_code_activated(kind) {
             let index = self.activated.iter().position(|r| r == kind).unwrap();
             self.activated.remove(index);
+            self.refresh_next_code();
+        }
+    }
+
+    // a new code is only rolled once the previous one is activated
+    fn refresh_next_code(&mut self) {
+        let next_done = self
+            .next_code
+            .map_or(true, |kind| self.is_code_activated(&kind));
+        if next_done {
+            self.next_code = self.roll_next_code();
         }
     }
 
@@ -170,6 +192,11 @@ impl CheatCodeResource {
         self.activated.contains(kind)
     }
 
+    /// Activated codes, from the oldest to the most recent
+    pub fn activated_codes(&self) -> &[CheatCodeKind] {
+        &self.activated
+    }
+
     pub fn new() -> Self {
         let mut codes: HashMap<CheatCodeKind, CheatCode> = HashMap::new();
 
@@ -180,7 +207,7 @@ impl CheatCodeResource {
             CheatCodeRarity::Mandatory,
             vec![],
             "jump.png",
-            "Press space to jump",
+            "Press {Jump} to jump",
         );
 
         // Common
@@ -190,7 +217,7 @@ impl CheatCodeResource {
             CheatCodeRarity::Common,
             vec![],
             "crouch.png",
-            "Press Ctrl to crouch",
+            "Press {Crouch} to crouch",
         );
         /*
         insert_cheat(
@@ -224,7 +251,7 @@ impl CheatCodeResource {
             CheatCodeRarity::Common,
             vec![],
             "move_left.png",
-            "Press 'A' to move left",
+            "Press {MoveLeft} to move left",
         );
         insert_cheat(
             &mut codes,
@@ -265,7 +292,7 @@ impl CheatCodeResource {
             CheatCodeRarity::Common,
             vec![],
             "dash.png",
-            "Double tap 'D' to dash",
+            "Double tap {MoveRight} or press {Dash} to dash",
         );
 
         // Rare
@@ -275,7 +302,7 @@ impl CheatCodeResource {
             CheatCodeRarity::Rare,
             vec![CheatCodeKind::Jump],
             "double_jump.png",
-            "Press space in air to jump",
+            "Press {Jump} in air to jump",
         );
         /*insert_cheat(
             &mut codes,
@@ -334,13 +361,16 @@ impl CheatCodeResource {
             CheatCodeRarity::Legendary,
             vec![CheatCodeKind::Jump, CheatCodeKind::DoubleJump],
             "fly.png",
-            "Hold space to fly",
+            "Hold {Jump} to fly",
         );*/
 
-        Self {
+        let mut resource = Self {
             codes,
             activated: Vec::new(),
-        }
+            next_code: None,
+        };
+        resource.refresh_next_code();
+        resource
     }
 }
 
@@ -378,17 +408,3 @@ pub fn generate_random_code(rarity: CheatCodeRarity) -> String {
         .sample_string(&mut rand::thread_rng(), length)
         .to_lowercase()
 }
-
-pub fn randomize_text(s: &String, indices: Vec<u8>, is_random_string: bool) -> String {
-    if !is_random_string {
-        let mut result = vec![' '; s.len()];
-        for (i, c) in indices.into_iter().zip(s.chars()) {
-            result[i as usize] = c;
-        }
-        return result.into_iter().collect();
-    } else {
-        return Alphanumeric
-            .sample_string(&mut rand::thread_rng(), s.len())
-            .to_lowercase();
-    }
-}
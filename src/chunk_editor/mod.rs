@@ -0,0 +1,592 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use bevy::prelude::*;
+use ron::ser::{to_string_pretty, PrettyConfig};
+use serde::Serialize;
+
+use crate::camera::TwoDCameraComponent;
+use crate::enemies::{EnemyKind, EnemyKindsResource};
+use crate::hazards::HazardKind;
+use crate::platforms::chunk::{
+    CharData, Chunk, ChunksResource, EnemyData, HazardData, PlatformData,
+};
+use crate::platforms::data::DataResource;
+use crate::platforms::platform::{PlatformBehavior, PlatformKind, PlatformKindsResource};
+use crate::platforms::reachability::chunk_surfaces;
+use crate::states::GameStates;
+use crate::toast::ShowToast;
+
+mod preview;
+mod ui;
+
+const GRID_SIZES: [f32; 4] = [16.0, 10.0, 5.0, 1.0];
+// in pixels per second
+const CAMERA_SPEED: f32 = 800.0;
+const DEFAULT_NEXT_CHUNK_OFFSET: f32 = 576.0;
+const DEFAULT_CHUNK_OFFSET: f32 = 80.0;
+
+/// Debug state to lay out the chunks of `assets/data/chunks.ron` with the mouse
+pub struct ChunkEditorPlugin;
+
+impl Plugin for ChunkEditorPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ChunkEditor::new())
+            .add_system_set(
+                SystemSet::on_enter(GameStates::ChunkEditor)
+                    .with_system(setup_editor)
+                    .with_system(ui::build_ui),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameStates::ChunkEditor)
+                    .with_system(editor_keyboard.label("editor_input"))
+                    .with_system(editor_mouse.label("editor_input"))
+                    .with_system(preview::draw_chunk.after("editor_input"))
+                    .with_system(ui::update_info_text.after("editor_input")),
+            )
+            .add_system_set(
+                SystemSet::on_exit(GameStates::ChunkEditor).with_system(destroy_editor_entities),
+            );
+    }
+}
+
+#[derive(Component)]
+pub struct ChunkEditorEntity;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EditorTool {
+    Platform,
+    Enemy,
+    Terminal,
+    Letter,
+    Hazard,
+}
+
+/// An element of the edited chunk
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ElementRef {
+    Platform(usize),
+    Enemy(usize),
+    Terminal(usize),
+    Hazard(usize),
+    // index of the char data, then of the position
+    Letter(usize, usize),
+}
+
+pub struct ChunkEditor {
+    // prelude chunks first, then the random ones
+    pub chunk_index: usize,
+    pub tool: EditorTool,
+    pub platform_kind_index: usize,
+    pub enemy_kind_index: usize,
+    pub hazard_index: usize,
+    pub grid_index: usize,
+    pub dragging: Option<ElementRef>,
+    pub needs_redraw: bool,
+    pub unsaved: bool,
+}
+
+impl ChunkEditor {
+    fn new() -> Self {
+        Self {
+            chunk_index: 0,
+            tool: EditorTool::Platform,
+            platform_kind_index: 0,
+            enemy_kind_index: 0,
+            hazard_index: 0,
+            grid_index: 0,
+            dragging: None,
+            needs_redraw: true,
+            unsaved: false,
+        }
+    }
+
+    pub fn grid_size(&self) -> f32 {
+        GRID_SIZES[self.grid_index]
+    }
+
+    fn snap(&self, position: Vec2) -> Vec2 {
+        (position / self.grid_size()).round() * self.grid_size()
+    }
+}
+
+pub fn chunk_count(chunks_resource: &ChunksResource) -> usize {
+    chunks_resource.prelude_chunks.len() + chunks_resource.chunks.len()
+}
+
+pub fn chunk_name(chunks_resource: &ChunksResource, index: usize) -> String {
+    let prelude_count = chunks_resource.prelude_chunks.len();
+    if index < prelude_count {
+        format!("prelude_chunks[{}]", index)
+    } else {
+        format!("chunks[{}]", index - prelude_count)
+    }
+}
+
+pub fn get_chunk(chunks_resource: &ChunksResource, index: usize) -> Option<&Chunk> {
+    let prelude_count = chunks_resource.prelude_chunks.len();
+    if index < prelude_count {
+        chunks_resource.prelude_chunks.get(index)
+    } else {
+        chunks_resource.chunks.get(index - prelude_count)
+    }
+}
+
+fn get_chunk_mut(chunks_resource: &mut ChunksResource, index: usize) -> Option<&mut Chunk> {
+    let prelude_count = chunks_resource.prelude_chunks.len();
+    if index < prelude_count {
+        chunks_resource.prelude_chunks.get_mut(index)
+    } else {
+        chunks_resource.chunks.get_mut(index - prelude_count)
+    }
+}
+
+/// Rightmost edge of the platforms of the chunk
+pub fn chunk_width(chunk: &Chunk, platform_kinds: &PlatformKindsResource) -> Option<f32> {
+    chunk_surfaces(chunk, platform_kinds)
+        .ok()?
+        .iter()
+        .map(|surface| surface.right)
+        .reduce(f32::max)
+}
+
+/// Hazards placed by the hazard tool, with their size in pixels
+pub fn hazard_presets() -> Vec<(HazardKind, Vec2)> {
+    vec![
+        (HazardKind::Spikes, Vec2::new(96.0, 12.0)),
+        (
+            HazardKind::Laser {
+                on_time: 2.0,
+                off_time: 1.5,
+            },
+            Vec2::new(48.0, 6.0),
+        ),
+        (
+            HazardKind::ElectrifiedFloor {
+                on_time: 1.5,
+                off_time: 1.5,
+            },
+            Vec2::new(120.0, 12.0),
+        ),
+    ]
+}
+
+/// Platform kinds sorted by name, so that they always cycle in the same order
+pub fn sorted_platform_kinds(platform_kinds: &PlatformKindsResource) -> Vec<&PlatformKind> {
+    let mut kinds: Vec<&PlatformKind> = platform_kinds.kinds.keys().collect();
+    kinds.sort_by(|a, b| a.0.cmp(&b.0));
+    kinds
+}
+
+/// Enemy kinds sorted by name, like the platform kinds
+pub fn sorted_enemy_kinds(enemy_kinds: &EnemyKindsResource) -> Vec<&EnemyKind> {
+    let mut kinds: Vec<&EnemyKind> = enemy_kinds.kinds.keys().collect();
+    kinds.sort_by(|a, b| a.0.cmp(&b.0));
+    kinds
+}
+
+/// Half size of the clickable area of an element
+pub fn element_half_extents(
+    chunk: &Chunk,
+    element: ElementRef,
+    platform_kinds: &PlatformKindsResource,
+) -> Vec2 {
+    match element {
+        ElementRef::Platform(i) => platform_kinds
+            .kinds
+            .get(&chunk.platforms[i].platform_kind)
+            .map_or(Vec2::new(16.0, 16.0), |kind_data| kind_data.half_extents),
+        ElementRef::Enemy(_) => Vec2::new(20.0, 15.0),
+        ElementRef::Terminal(_) => Vec2::new(45.0, 30.0),
+        ElementRef::Hazard(i) => chunk.hazards[i].size / 2.0,
+        ElementRef::Letter(_, _) => Vec2::new(10.0, 10.0),
+    }
+}
+
+pub fn element_position(chunk: &Chunk, element: ElementRef) -> Vec2 {
+    match element {
+        ElementRef::Platform(i) => chunk.platforms[i].position,
+        ElementRef::Enemy(i) => chunk.enemies[i].position,
+        ElementRef::Terminal(i) => chunk.terminals[i],
+        ElementRef::Hazard(i) => chunk.hazards[i].position,
+        ElementRef::Letter(i, j) => chunk.chars[i].positions[j],
+    }
+}
+
+/// Every element of the chunk, the ones drawn on top first
+pub fn chunk_elements(chunk: &Chunk) -> Vec<ElementRef> {
+    let mut elements = Vec::new();
+
+    for (i, ch_data) in chunk.chars.iter().enumerate() {
+        for j in 0..ch_data.positions.len() {
+            elements.push(ElementRef::Letter(i, j));
+        }
+    }
+    elements.extend((0..chunk.terminals.len()).map(ElementRef::Terminal));
+    elements.extend((0..chunk.enemies.len()).map(ElementRef::Enemy));
+    elements.extend((0..chunk.hazards.len()).map(ElementRef::Hazard));
+    elements.extend((0..chunk.platforms.len()).rev().map(ElementRef::Platform));
+
+    elements
+}
+
+fn set_element_position(chunk: &mut Chunk, element: ElementRef, position: Vec2) {
+    match element {
+        ElementRef::Platform(i) => chunk.platforms[i].position = position,
+        ElementRef::Enemy(i) => chunk.enemies[i].position = position,
+        ElementRef::Terminal(i) => chunk.terminals[i] = position,
+        ElementRef::Hazard(i) => chunk.hazards[i].position = position,
+        ElementRef::Letter(i, j) => chunk.chars[i].positions[j] = position,
+    }
+}
+
+fn remove_element(chunk: &mut Chunk, element: ElementRef) {
+    match element {
+        ElementRef::Platform(i) => {
+            chunk.platforms.remove(i);
+        }
+        ElementRef::Enemy(i) => {
+            chunk.enemies.remove(i);
+        }
+        ElementRef::Terminal(i) => {
+            chunk.terminals.remove(i);
+        }
+        ElementRef::Hazard(i) => {
+            chunk.hazards.remove(i);
+        }
+        ElementRef::Letter(i, j) => {
+            chunk.chars[i].positions.remove(j);
+            if chunk.chars[i].positions.is_empty() {
+                chunk.chars.remove(i);
+            }
+        }
+    }
+}
+
+fn element_at(
+    chunk: &Chunk,
+    position: Vec2,
+    platform_kinds: &PlatformKindsResource,
+) -> Option<ElementRef> {
+    chunk_elements(chunk).into_iter().find(|element| {
+        let offset = (position - element_position(chunk, *element)).abs();
+        let half_extents = element_half_extents(chunk, *element, platform_kinds);
+        offset.x <= half_extents.x && offset.y <= half_extents.y
+    })
+}
+
+fn add_element(
+    chunk: &mut Chunk,
+    editor: &ChunkEditor,
+    position: Vec2,
+    platform_kinds: &PlatformKindsResource,
+    enemy_kinds: &EnemyKindsResource,
+) -> Option<ElementRef> {
+    match editor.tool {
+        EditorTool::Platform => {
+            let kinds = sorted_platform_kinds(platform_kinds);
+            let platform_kind = (*kinds.get(editor.platform_kind_index)?).clone();
+            chunk.platforms.push(PlatformData {
+                platform_kind,
+                position,
+                behavior: PlatformBehavior::Static,
+            });
+            Some(ElementRef::Platform(chunk.platforms.len() - 1))
+        }
+        EditorTool::Enemy => {
+            let kinds = sorted_enemy_kinds(enemy_kinds);
+            let enemy_kind = (*kinds.get(editor.enemy_kind_index)?).clone();
+            chunk.enemies.push(EnemyData {
+                enemy_kind,
+                position,
+            });
+            Some(ElementRef::Enemy(chunk.enemies.len() - 1))
+        }
+        EditorTool::Terminal => {
+            chunk.terminals.push(position);
+            Some(ElementRef::Terminal(chunk.terminals.len() - 1))
+        }
+        EditorTool::Hazard => {
+            let (hazard_kind, size) = hazard_presets().swap_remove(editor.hazard_index);
+            chunk.hazards.push(HazardData {
+                hazard_kind,
+                position,
+                size,
+            });
+            Some(ElementRef::Hazard(chunk.hazards.len() - 1))
+        }
+        EditorTool::Letter => {
+            chunk.chars.push(CharData {
+                cheat_kind: None,
+                positions: vec![position],
+                is_random: true,
+            });
+            Some(ElementRef::Letter(chunk.chars.len() - 1, 0))
+        }
+    }
+}
+
+fn setup_editor(
+    mut editor: ResMut<ChunkEditor>,
+    mut camera_query: Query<&mut Transform, With<TwoDCameraComponent>>,
+) {
+    info!("[ChunkEditorPlugin] Entering the chunk editor");
+
+    *editor = ChunkEditor::new();
+
+    for mut transform in camera_query.iter_mut() {
+        transform.translation.x = 0.0;
+    }
+}
+
+fn editor_keyboard(
+    keyboard: Res<Input<KeyCode>>,
+    time: Res<Time>,
+    mut editor: ResMut<ChunkEditor>,
+    mut chunks_resource: ResMut<ChunksResource>,
+    platform_kinds: Res<PlatformKindsResource>,
+    enemy_kinds: Res<EnemyKindsResource>,
+    mut camera_query: Query<&mut Transform, With<TwoDCameraComponent>>,
+    mut game_state: ResMut<State<GameStates>>,
+    mut toast_writer: EventWriter<ShowToast>,
+) {
+    let tools = [
+        (KeyCode::Key1, EditorTool::Platform),
+        (KeyCode::Key2, EditorTool::Enemy),
+        (KeyCode::Key3, EditorTool::Terminal),
+        (KeyCode::Key4, EditorTool::Letter),
+        (KeyCode::Key5, EditorTool::Hazard),
+    ];
+    for (key, tool) in tools {
+        if keyboard.just_pressed(key) {
+            editor.tool = tool;
+        }
+    }
+
+    // the enemy and hazard tools have their own kinds
+    let (kind_index, kind_count) = match editor.tool {
+        EditorTool::Enemy => (&mut editor.enemy_kind_index, enemy_kinds.kinds.len().max(1)),
+        EditorTool::Hazard => (&mut editor.hazard_index, hazard_presets().len()),
+        _ => (
+            &mut editor.platform_kind_index,
+            platform_kinds.kinds.len().max(1),
+        ),
+    };
+    if keyboard.just_pressed(KeyCode::Q) {
+        *kind_index = (*kind_index + kind_count - 1) % kind_count;
+    }
+    if keyboard.just_pressed(KeyCode::E) {
+        *kind_index = (*kind_index + 1) % kind_count;
+    }
+
+    if keyboard.just_pressed(KeyCode::G) {
+        editor.grid_index = (editor.grid_index + 1) % GRID_SIZES.len();
+    }
+
+    // switching chunk
+    let chunk_count = chunk_count(&chunks_resource);
+    if keyboard.just_pressed(KeyCode::PageUp) && chunk_count > 0 {
+        editor.chunk_index = (editor.chunk_index + chunk_count - 1) % chunk_count;
+        editor.dragging = None;
+        editor.needs_redraw = true;
+    }
+    if keyboard.just_pressed(KeyCode::PageDown) && chunk_count > 0 {
+        editor.chunk_index = (editor.chunk_index + 1) % chunk_count;
+        editor.dragging = None;
+        editor.needs_redraw = true;
+    }
+    if keyboard.just_pressed(KeyCode::N) {
+        chunks_resource.chunks.push(Chunk {
+            platforms: Vec::new(),
+            enemies: Vec::new(),
+            hazards: Vec::new(),
+            terminals: Vec::new(),
+            chars: Vec::new(),
+            required_cheats: Vec::new(),
+            rewarding_cheats: Vec::new(),
+            biomes: Vec::new(),
+            next_chunk_offset: DEFAULT_NEXT_CHUNK_OFFSET,
+            chunk_offset: DEFAULT_CHUNK_OFFSET,
+            difficulty: 1,
+        });
+        editor.chunk_index = chunk_count;
+        editor.dragging = None;
+        editor.unsaved = true;
+    }
+
+    // chunk settings
+    let grid_size = editor.grid_size();
+    let chunk_index = editor.chunk_index;
+    let chunk_keys = [
+        KeyCode::Equals,
+        KeyCode::Minus,
+        KeyCode::RBracket,
+        KeyCode::LBracket,
+        KeyCode::D,
+    ];
+    let chunk_key_pressed = chunk_keys.iter().any(|key| keyboard.just_pressed(*key));
+    if chunk_key_pressed {
+        if let Some(chunk) = get_chunk_mut(&mut chunks_resource, chunk_index) {
+            if keyboard.just_pressed(KeyCode::Equals) {
+                chunk.next_chunk_offset += grid_size;
+            } else if keyboard.just_pressed(KeyCode::Minus) {
+                chunk.next_chunk_offset = (chunk.next_chunk_offset - grid_size).max(0.0);
+            } else if keyboard.just_pressed(KeyCode::RBracket) {
+                chunk.chunk_offset += grid_size;
+            } else if keyboard.just_pressed(KeyCode::LBracket) {
+                chunk.chunk_offset = (chunk.chunk_offset - grid_size).max(0.0);
+            } else if keyboard.just_pressed(KeyCode::D) {
+                chunk.difficulty = chunk.difficulty % 3 + 1;
+            }
+            editor.unsaved = true;
+        }
+    }
+
+    // scrolling
+    let direction = keyboard.pressed(KeyCode::Right) as i8 - keyboard.pressed(KeyCode::Left) as i8;
+    for mut transform in camera_query.iter_mut() {
+        transform.translation.x += direction as f32 * CAMERA_SPEED * time.delta_seconds();
+    }
+
+    let control = keyboard.pressed(KeyCode::LControl) || keyboard.pressed(KeyCode::RControl);
+    if control && keyboard.just_pressed(KeyCode::S) {
+        match save_chunks(&chunks_resource) {
+            Ok(path) => {
+                info!("[ChunkEditorPlugin] Saved {}", path.display());
+                editor.unsaved = false;
+                toast_writer.send(ShowToast {
+                    value: format!("Saved {}", ChunksResource::PATH),
+                    duration: Duration::from_secs(2),
+                });
+            }
+            Err(error) => {
+                error!("[ChunkEditorPlugin] Cannot save the chunks: {}", error);
+                toast_writer.send(ShowToast {
+                    value: format!("Cannot save the chunks: {}", error),
+                    duration: Duration::from_secs(5),
+                });
+            }
+        }
+    }
+
+    if keyboard.just_pressed(KeyCode::Escape) {
+        game_state.set(GameStates::MainMenu).unwrap();
+    }
+}
+
+fn editor_mouse(
+    mouse: Res<Input<MouseButton>>,
+    windows: Res<Windows>,
+    mut editor: ResMut<ChunkEditor>,
+    mut chunks_resource: ResMut<ChunksResource>,
+    platform_kinds: Res<PlatformKindsResource>,
+    enemy_kinds: Res<EnemyKindsResource>,
+    camera_query: Query<&Transform, With<TwoDCameraComponent>>,
+) {
+    let window = match windows.get_primary() {
+        Some(window) => window,
+        None => return,
+    };
+    let (cursor, camera) = match (window.cursor_position(), camera_query.iter().next()) {
+        (Some(cursor), Some(camera)) => (cursor, camera),
+        _ => return,
+    };
+
+    // the 2d camera is centered on the window
+    let world_position =
+        cursor - Vec2::new(window.width(), window.height()) / 2.0 + camera.translation.truncate();
+    let snapped_position = editor.snap(world_position);
+
+    // only borrowed mutably on changes, to redraw the preview when needed
+    let chunk_index = editor.chunk_index;
+    let hovered = match get_chunk(&chunks_resource, chunk_index) {
+        Some(chunk) => element_at(chunk, world_position, &platform_kinds),
+        None => return,
+    };
+
+    if mouse.just_pressed(MouseButton::Left) {
+        editor.dragging = match hovered {
+            Some(element) => Some(element),
+            None => {
+                editor.unsaved = true;
+                get_chunk_mut(&mut chunks_resource, chunk_index).and_then(|chunk| {
+                    add_element(
+                        chunk,
+                        &editor,
+                        snapped_position,
+                        &platform_kinds,
+                        &enemy_kinds,
+                    )
+                })
+            }
+        };
+    } else if mouse.just_released(MouseButton::Left) {
+        editor.dragging = None;
+    } else if mouse.just_pressed(MouseButton::Right) {
+        if let Some(element) = hovered {
+            if let Some(chunk) = get_chunk_mut(&mut chunks_resource, chunk_index) {
+                remove_element(chunk, element);
+                editor.dragging = None;
+                editor.unsaved = true;
+            }
+        }
+    }
+
+    if let Some(element) = editor.dragging {
+        let moved = get_chunk(&chunks_resource, chunk_index).map_or(false, |chunk| {
+            element_position(chunk, element) != snapped_position
+        });
+
+        if moved {
+            if let Some(chunk) = get_chunk_mut(&mut chunks_resource, chunk_index) {
+                set_element_position(chunk, element, snapped_position);
+                editor.unsaved = true;
+            }
+        }
+    }
+}
+
+/// Layout of `assets/data/chunks.ron`
+#[derive(Serialize)]
+struct ChunksFile<'a> {
+    furthest_x: f32,
+    prelude_chunks: &'a [Chunk],
+    chunks: &'a [Chunk],
+}
+
+fn save_chunks(chunks_resource: &ChunksResource) -> Result<PathBuf, String> {
+    let chunks_file = ChunksFile {
+        furthest_x: 0.0,
+        prelude_chunks: &chunks_resource.prelude_chunks,
+        chunks: &chunks_resource.chunks,
+    };
+    let content = to_string_pretty(
+        &chunks_file,
+        PrettyConfig::new().with_indentor("\t".to_string()),
+    )
+    .map_err(|error| error.to_string())?;
+
+    // same root as the asset server
+    let root =
+        std::env::var("CARGO_MANIFEST_DIR").map_or_else(|_| PathBuf::from("."), PathBuf::from);
+    let path = root.join("assets").join(ChunksResource::PATH);
+    std::fs::write(&path, content + "\n").map_err(|error| error.to_string())?;
+
+    Ok(path)
+}
+
+fn destroy_editor_entities(
+    mut commands: Commands,
+    query: Query<Entity, With<ChunkEditorEntity>>,
+    mut camera_query: Query<&mut Transform, With<TwoDCameraComponent>>,
+) {
+    info!("[ChunkEditorPlugin] Destroying state entities before exiting...");
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    for mut transform in camera_query.iter_mut() {
+        transform.translation.x = 0.0;
+    }
+    info!("[ChunkEditorPlugin] Exiting state");
+}
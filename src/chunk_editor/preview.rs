@@ -0,0 +1,150 @@
+use bevy::prelude::*;
+
+use super::{
+    chunk_elements, chunk_width, element_half_extents, element_position, get_chunk, ChunkEditor,
+    ChunkEditorEntity, ElementRef,
+};
+use crate::platforms::chunk::ChunksResource;
+use crate::platforms::platform::{PlatformBehavior, PlatformKindsResource};
+
+const MARKER_HEIGHT: f32 = 720.0;
+
+/// Sprites of the edited chunk, rebuilt after every change
+#[derive(Component)]
+pub struct PreviewEntity;
+
+pub fn draw_chunk(
+    mut commands: Commands,
+    mut editor: ResMut<ChunkEditor>,
+    chunks_resource: Res<ChunksResource>,
+    platform_kinds: Res<PlatformKindsResource>,
+    asset_server: Res<AssetServer>,
+    preview_query: Query<Entity, With<PreviewEntity>>,
+) {
+    if !editor.needs_redraw && !chunks_resource.is_changed() {
+        return;
+    }
+    editor.needs_redraw = false;
+
+    for entity in preview_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    let chunk = match get_chunk(&chunks_resource, editor.chunk_index) {
+        Some(chunk) => chunk,
+        None => return,
+    };
+
+    for element in chunk_elements(chunk) {
+        let position = element_position(chunk, element);
+        let half_extents = element_half_extents(chunk, element, &platform_kinds);
+
+        match element {
+            ElementRef::Platform(i) => {
+                let platform_data = &chunk.platforms[i];
+                if let Some(kind_data) = platform_kinds.kinds.get(&platform_data.platform_kind) {
+                    spawn_sprite(
+                        &mut commands,
+                        SpriteBundle {
+                            texture: asset_server.load(kind_data.sprite.as_str()),
+                            transform: Transform::from_translation(
+                                (position + kind_data.sprite_offset).extend(50.0),
+                            ),
+                            ..Default::default()
+                        },
+                    );
+                }
+                spawn_box(
+                    &mut commands,
+                    position,
+                    half_extents,
+                    Color::rgba(1.0, 1.0, 1.0, 0.2),
+                    51.0,
+                );
+
+                // path of the moving platforms
+                if let PlatformBehavior::Moving { waypoints, .. } = &platform_data.behavior {
+                    for waypoint in waypoints.iter() {
+                        spawn_box(
+                            &mut commands,
+                            position + *waypoint,
+                            half_extents,
+                            Color::rgba(0.3, 0.6, 1.0, 0.3),
+                            51.0,
+                        );
+                    }
+                }
+            }
+            ElementRef::Enemy(_) => spawn_box(
+                &mut commands,
+                position,
+                half_extents,
+                Color::rgba(1.0, 0.2, 0.2, 0.8),
+                60.0,
+            ),
+            ElementRef::Terminal(_) => spawn_box(
+                &mut commands,
+                position,
+                half_extents,
+                Color::rgba(0.2, 0.4, 1.0, 0.8),
+                60.0,
+            ),
+            ElementRef::Hazard(_) => spawn_box(
+                &mut commands,
+                position,
+                half_extents,
+                Color::rgba(1.0, 0.0, 1.0, 0.8),
+                60.0,
+            ),
+            ElementRef::Letter(i, _) => {
+                // letters of a cheat code are orange, random ones yellow
+                let color = if chunk.chars[i].cheat_kind.is_some() {
+                    Color::rgba(1.0, 0.5, 0.0, 0.9)
+                } else {
+                    Color::rgba(1.0, 1.0, 0.0, 0.9)
+                };
+                spawn_box(&mut commands, position, half_extents, color, 70.0);
+            }
+        }
+    }
+
+    // chunk boundaries
+    spawn_marker(&mut commands, 0.0, Color::WHITE);
+    spawn_marker(&mut commands, -chunk.chunk_offset, Color::ORANGE);
+    spawn_marker(&mut commands, chunk.next_chunk_offset, Color::GREEN);
+    if let Some(right) = chunk_width(chunk, &platform_kinds) {
+        spawn_marker(&mut commands, right, Color::GRAY);
+    }
+}
+
+fn spawn_sprite(commands: &mut Commands, sprite_bundle: SpriteBundle) {
+    commands
+        .spawn_bundle(sprite_bundle)
+        .insert(PreviewEntity)
+        .insert(ChunkEditorEntity);
+}
+
+fn spawn_box(commands: &mut Commands, position: Vec2, half_extents: Vec2, color: Color, z: f32) {
+    spawn_sprite(
+        commands,
+        SpriteBundle {
+            sprite: Sprite {
+                color,
+                custom_size: Some(half_extents * 2.0),
+                ..Default::default()
+            },
+            transform: Transform::from_translation(position.extend(z)),
+            ..Default::default()
+        },
+    );
+}
+
+fn spawn_marker(commands: &mut Commands, x: f32, color: Color) {
+    spawn_box(
+        commands,
+        Vec2::new(x, 0.0),
+        Vec2::new(1.0, MARKER_HEIGHT / 2.0),
+        color,
+        80.0,
+    );
+}
@@ -0,0 +1,109 @@
+use bevy::prelude::*;
+
+use super::{
+    chunk_count, chunk_name, chunk_width, get_chunk, hazard_presets, sorted_enemy_kinds,
+    sorted_platform_kinds, ChunkEditor, ChunkEditorEntity, EditorTool,
+};
+use crate::enemies::EnemyKindsResource;
+use crate::platforms::chunk::ChunksResource;
+use crate::platforms::platform::PlatformKindsResource;
+
+const HELP: &str =
+    "1-5: tool | Q/E: platform / enemy / hazard kind | G: grid | PageUp/PageDown: chunk | N: new chunk\n\
+    -/=: next_chunk_offset | [/]: chunk_offset | D: difficulty | arrows: scroll\n\
+    left click: place / move | right click: delete | Ctrl+S: save | Esc: quit";
+
+#[derive(Component)]
+pub struct EditorInfoText;
+
+pub fn build_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
+    info!("[ChunkEditorPlugin] Building UI");
+
+    let font = asset_server.load("fonts/VT323-Regular.ttf");
+    let style = TextStyle {
+        font,
+        font_size: 20.,
+        color: Color::WHITE,
+    };
+
+    commands
+        .spawn_bundle(TextBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    top: Val::Px(10.),
+                    left: Val::Px(10.),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            text: Text {
+                sections: vec![
+                    TextSection {
+                        value: String::new(),
+                        style: style.clone(),
+                    },
+                    TextSection {
+                        value: format!("\n{}", HELP),
+                        style: TextStyle {
+                            color: Color::GRAY,
+                            ..style
+                        },
+                    },
+                ],
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .insert(EditorInfoText)
+        .insert(ChunkEditorEntity);
+}
+
+pub fn update_info_text(
+    editor: Res<ChunkEditor>,
+    chunks_resource: Res<ChunksResource>,
+    platform_kinds: Res<PlatformKindsResource>,
+    enemy_kinds: Res<EnemyKindsResource>,
+    mut text_query: Query<&mut Text, With<EditorInfoText>>,
+) {
+    let chunk = match get_chunk(&chunks_resource, editor.chunk_index) {
+        Some(chunk) => chunk,
+        None => return,
+    };
+
+    let tool = match editor.tool {
+        EditorTool::Platform => format!(
+            "Platform ({})",
+            sorted_platform_kinds(&platform_kinds)
+                .get(editor.platform_kind_index)
+                .map_or("?", |kind| kind.0.as_str())
+        ),
+        EditorTool::Enemy => format!(
+            "Enemy ({})",
+            sorted_enemy_kinds(&enemy_kinds)
+                .get(editor.enemy_kind_index)
+                .map_or("?", |kind| kind.0.as_str())
+        ),
+        EditorTool::Hazard => format!("Hazard ({:?})", hazard_presets()[editor.hazard_index].0),
+        tool => format!("{:?}", tool),
+    };
+
+    let width = chunk_width(chunk, &platform_kinds).unwrap_or(0.0);
+
+    for mut text in text_query.iter_mut() {
+        text.sections[0].value = format!(
+            "{} ({}/{}){} | tool: {} | grid: {}px\n\
+            width: {:.0}px | next_chunk_offset: {} | chunk_offset: {} | difficulty: {}",
+            chunk_name(&chunks_resource, editor.chunk_index),
+            editor.chunk_index + 1,
+            chunk_count(&chunks_resource),
+            if editor.unsaved { " *" } else { "" },
+            tool,
+            editor.grid_size(),
+            width,
+            chunk.next_chunk_offset,
+            chunk.chunk_offset,
+            chunk.difficulty
+        );
+    }
+}
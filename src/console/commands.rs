@@ -1,12 +1,17 @@
 use crate::cheat_codes::CheatCodeActivationResult;
+use crate::interactables::{TerminalComponent, TerminalLockout, TERMINAL_MAX_FAILED_ATTEMPTS};
 use crate::runner::CollectedChars;
 use crate::{cheat_codes::CheatCodeResource, states::GameStates};
 use bevy_kira_audio::{Audio, AudioChannel};
+use rand::seq::SliceRandom;
 
-use super::{event::*, CheatCodeActivatedEvent, ConsoleData};
+use super::{event::*, markup::escape, ActiveTerminal, CheatCodeActivatedEvent, ConsoleData};
 use bevy::prelude::*;
 
+const FAILED_ATTEMPTS_LOCKOUT_TIME: f32 = 30.0;
+
 pub fn command_handler(
+    mut commands: Commands,
     mut cmd_reader: EventReader<SendCommandEvent>,
     mut print_to_console: EventWriter<PrintToConsoleEvent>,
     mut data: ResMut<ConsoleData>,
@@ -14,6 +19,9 @@ pub fn command_handler(
     mut cheat_codes_res: ResMut<CheatCodeResource>,
     mut collected_chars: ResMut<CollectedChars>,
     mut ev_writer: EventWriter<CheatCodeActivatedEvent>,
+    active_terminal: Res<ActiveTerminal>,
+    mut terminal_query: Query<&mut TerminalComponent>,
+    lockout_query: Query<&TerminalLockout>,
     audio: Res<Audio>,
     asset_server: Res<AssetServer>,
 ) {
@@ -28,9 +36,10 @@ pub fn command_handler(
 
         // show the command entered by the user if it's not a clear
         if args[0] != "clear" {
-            let mut user_input = String::from("> ");
-            user_input.push_str(command.clone().trim());
-            print_to_console.send(PrintToConsoleEvent(user_input));
+            print_to_console.send(PrintToConsoleEvent(format!(
+                "[dim]> {}[/]",
+                escape(command.trim())
+            )));
         }
 
         // dispatch the command
@@ -38,41 +47,137 @@ pub fn command_handler(
             "clear" => data.lines.clear(),
             "help" => print_to_console.send(PrintToConsoleEvent(super::utils::display_help())),
             "cheat" => {
+                let code_text = args.get(1).copied().unwrap_or("");
                 print_to_console.send(PrintToConsoleEvent(format!(
                     "Activating cheat code: <{}>...",
-                    args[1]
+                    escape(code_text)
                 )));
 
-                let can_activate = is_valid_cheat(&mut collected_chars, args[1], &cheat_codes_res);
+                let terminal_entity = match active_terminal.0 {
+                    Some(entity) => entity,
+                    None => continue,
+                };
+                let mut terminal = match terminal_query.get_mut(terminal_entity) {
+                    Ok(terminal) => terminal,
+                    Err(_) => continue,
+                };
+
+                if terminal.is_depleted() {
+                    print_to_console.send(PrintToConsoleEvent(
+                        "[red]This terminal has no activations left.[/]".to_string(),
+                    ));
+                    continue;
+                }
+
+                let can_activate =
+                    is_valid_cheat(&mut collected_chars, code_text, &cheat_codes_res);
 
                 if can_activate {
-                    let activation_res = cheat_codes_res.activate_code(args[1]);
+                    let activation_res = cheat_codes_res.activate_code(code_text);
+                    let color = match activation_res {
+                        CheatCodeActivationResult::Activated(_) => "yellow",
+                        _ => "red",
+                    };
                     print_to_console.send(PrintToConsoleEvent(format!(
-                        "Activation result: {}",
-                        activation_res.repr()
+                        "Activation result: [{}]{}[/]",
+                        color,
+                        escape(&activation_res.repr())
                     )));
 
                     if let CheatCodeActivationResult::Activated(kind) = activation_res {
+                        terminal.activations_left -= 1;
                         ev_writer.send(CheatCodeActivatedEvent(kind));
                         let audio_channel = AudioChannel::new("sfx-channel".to_owned());
                         audio.set_volume_in_channel(2.0, &audio_channel);
                         audio.play_in_channel(asset_server.load("powerup.ogg"), &audio_channel);
                     }
                 } else {
-                    print_to_console.send(PrintToConsoleEvent(format!(
-                        "Failed to activate. Need more information."
-                    )));
+                    print_to_console.send(PrintToConsoleEvent(
+                        "[red]Failed to activate. Need more information.[/]".to_string(),
+                    ));
+
+                    terminal.failed_attempts += 1;
+                    if terminal.failed_attempts >= TERMINAL_MAX_FAILED_ATTEMPTS {
+                        terminal.failed_attempts = 0;
+                        commands.entity(terminal_entity).insert(TerminalLockout(
+                            Timer::from_seconds(FAILED_ATTEMPTS_LOCKOUT_TIME, false),
+                        ));
+                        print_to_console.send(PrintToConsoleEvent(
+                            "[red]Too many failed attempts. Connection terminated.[/]".to_string(),
+                        ));
+                        game_state.pop().unwrap();
+                    }
                 }
             }
             "exit" => {
                 print_to_console.send(PrintToConsoleEvent("Closing session...".to_string()));
                 game_state.pop().unwrap();
             }
-            "log" => print_to_console.send(PrintToConsoleEvent(super::utils::display_random_log())),
+            "log" => {
+                if let Some(log) = data.logs.choose(&mut rand::thread_rng()) {
+                    print_to_console.send(PrintToConsoleEvent(log.clone()));
+                }
+            }
+            "ls" => {
+                let names = data
+                    .files
+                    .iter()
+                    .map(|(name, _)| name.clone())
+                    .collect::<Vec<String>>();
+                print_to_console.send(PrintToConsoleEvent(names.join("    ")));
+            }
+            "cat" => {
+                let name = args.get(1).copied().unwrap_or("");
+                match data.files.iter().find(|(file_name, _)| file_name == name) {
+                    Some((_, content)) => {
+                        print_to_console.send(PrintToConsoleEvent(escape(content)))
+                    }
+                    None => print_to_console.send(PrintToConsoleEvent(format!(
+                        "[red]cat: {}: No such file[/]",
+                        escape(name)
+                    ))),
+                }
+            }
+            "hack" => {
+                let terminal_entity = match active_terminal.0 {
+                    Some(entity) => entity,
+                    None => continue,
+                };
+                let terminal = match terminal_query.get(terminal_entity) {
+                    Ok(terminal) => terminal,
+                    Err(_) => continue,
+                };
+
+                if lockout_query.get(terminal_entity).is_ok() {
+                    print_to_console.send(PrintToConsoleEvent(
+                        "[red]This terminal is locked.[/]".to_string(),
+                    ));
+                    continue;
+                }
+                if terminal.is_depleted() {
+                    print_to_console.send(PrintToConsoleEvent(
+                        "[red]This terminal has no activations left.[/]".to_string(),
+                    ));
+                    continue;
+                }
+
+                match super::hack::HackSession::new(&cheat_codes_res) {
+                    Some(session) => {
+                        print_to_console.send(PrintToConsoleEvent(session.intro()));
+                        print_to_console.send(PrintToConsoleEvent(
+                            "Type 'abort' to give up (the terminal will lock).".to_string(),
+                        ));
+                        data.hack = Some(session);
+                    }
+                    None => print_to_console.send(PrintToConsoleEvent(
+                        "[yellow]Nothing left to hack: every code is activated.[/]".to_string(),
+                    )),
+                }
+            }
             _ => {
                 print_to_console.send(PrintToConsoleEvent(format!(
-                    "Command \"{}\" not found.\nType \"help\" to print the list of available commands.",
-                    args[0]
+                    "[red]Command \"{}\" not found.[/]\nType \"help\" to print the list of available commands.",
+                    escape(args[0])
                 )));
             }
         }
@@ -0,0 +1,280 @@
+use bevy::prelude::*;
+use rand::{seq::SliceRandom, Rng};
+
+use super::{event::PrintToConsoleEvent, markup::escape, ActiveTerminal, ConsoleData};
+use crate::cheat_codes::CheatCodeResource;
+use crate::interactables::{TerminalComponent, TerminalLockout};
+use crate::runner::CollectedChars;
+use crate::states::GameStates;
+
+const MASTERMIND_SYMBOLS: [char; 6] = ['1', '2', '3', '4', '5', '6'];
+const MASTERMIND_LENGTH: usize = 4;
+const MASTERMIND_ATTEMPTS: u8 = 8;
+const MASTERMIND_TIME: f32 = 60.0;
+
+const ANAGRAM_ATTEMPTS: u8 = 3;
+const ANAGRAM_TIME: f32 = 30.0;
+
+// number of letters of the solved code given to the player
+const REWARD_LETTERS: usize = 2;
+const LOCKOUT_TIME: f32 = 20.0;
+
+pub struct HackInputEvent(pub String);
+
+pub enum HackPuzzle {
+    // guess a sequence of symbols, with exact/misplaced hints after each try
+    Mastermind { secret: Vec<char> },
+    // unscramble the text of a cheat code
+    Anagram { answer: String, scrambled: String },
+}
+
+pub struct HackSession {
+    pub puzzle: HackPuzzle,
+    pub attempts_left: u8,
+    pub timer: Timer,
+    // letters given to the player when the puzzle is solved
+    reward: Vec<char>,
+}
+
+impl HackSession {
+    /// Session rewarding letters of the next code, none once every code is activated
+    pub fn new(cheat_codes: &CheatCodeResource) -> Option<Self> {
+        let mut rng = rand::thread_rng();
+
+        // the reward is always taken from the code the player should go for next
+        let next_code = cheat_codes.get_next_code()?;
+        let code_text = cheat_codes.codes.get(&next_code).unwrap().text.clone();
+        let reward = code_text
+            .chars()
+            .collect::<Vec<char>>()
+            .choose_multiple(&mut rng, REWARD_LETTERS)
+            .cloned()
+            .collect();
+
+        if rng.gen_bool(0.5) {
+            let secret = (0..MASTERMIND_LENGTH)
+                .map(|_| *MASTERMIND_SYMBOLS.choose(&mut rng).unwrap())
+                .collect();
+
+            Some(Self {
+                puzzle: HackPuzzle::Mastermind { secret },
+                attempts_left: MASTERMIND_ATTEMPTS,
+                timer: Timer::from_seconds(MASTERMIND_TIME, false),
+                reward,
+            })
+        } else {
+            let mut scrambled: Vec<char> = code_text.chars().collect();
+            while scrambled.iter().collect::<String>() == code_text && scrambled.len() > 1 {
+                scrambled.shuffle(&mut rng);
+            }
+
+            Some(Self {
+                puzzle: HackPuzzle::Anagram {
+                    answer: code_text,
+                    scrambled: scrambled.into_iter().collect(),
+                },
+                attempts_left: ANAGRAM_ATTEMPTS,
+                timer: Timer::from_seconds(ANAGRAM_TIME, false),
+                reward,
+            })
+        }
+    }
+
+    pub fn intro(&self) -> String {
+        match &self.puzzle {
+            HackPuzzle::Mastermind { .. } => format!(
+                "+ BREACH PROTOCOL ----------------------------------------------------------  +\n\
+                 Guess the {} digit access key (digits 1 to {}).\n\
+                 After each guess: [[exact] right digit at the right place,\n\
+                 [[misplaced] right digit at the wrong place.\n\
+                 {} attempts, {} seconds.",
+                MASTERMIND_LENGTH,
+                MASTERMIND_SYMBOLS.len(),
+                self.attempts_left,
+                self.timer.duration().as_secs()
+            ),
+            HackPuzzle::Anagram { scrambled, .. } => format!(
+                "+ BREACH PROTOCOL ----------------------------------------------------------  +\n\
+                 Corrupted cheat code recovered: <[b]{}[/]>\n\
+                 Unscramble it to restore the data.\n\
+                 {} attempts, {} seconds.",
+                scrambled,
+                self.attempts_left,
+                self.timer.duration().as_secs()
+            ),
+        }
+    }
+
+    pub fn seconds_left(&self) -> u64 {
+        (self.timer.duration() - self.timer.elapsed()).as_secs()
+    }
+
+    /// Checks a guess and returns if the puzzle is solved and the hint to display
+    fn guess(&mut self, guess: &str) -> (bool, String) {
+        self.attempts_left = self.attempts_left.saturating_sub(1);
+
+        match &self.puzzle {
+            HackPuzzle::Mastermind { secret } => {
+                let guess: Vec<char> = guess.chars().collect();
+                if guess.len() != secret.len() {
+                    // a malformed guess doesn't cost an attempt
+                    self.attempts_left += 1;
+                    return (
+                        false,
+                        format!("[red]The key is {} digits long.[/]", secret.len()),
+                    );
+                }
+
+                let exact = guess.iter().zip(secret).filter(|(a, b)| a == b).count();
+                let common: usize = MASTERMIND_SYMBOLS
+                    .iter()
+                    .map(|symbol| {
+                        let in_guess = guess.iter().filter(|c| *c == symbol).count();
+                        let in_secret = secret.iter().filter(|c| *c == symbol).count();
+                        in_guess.min(in_secret)
+                    })
+                    .sum();
+
+                (
+                    exact == secret.len(),
+                    format!(
+                        "[[exact] [b]{}[/] [[misplaced] [b]{}[/] ({} attempts left)",
+                        exact,
+                        common - exact,
+                        self.attempts_left
+                    ),
+                )
+            }
+            HackPuzzle::Anagram { answer, .. } => (
+                guess == answer,
+                format!(
+                    "[red]Checksum mismatch.[/] ({} attempts left)",
+                    self.attempts_left
+                ),
+            ),
+        }
+    }
+}
+
+pub fn handle_hack_input(
+    mut commands: Commands,
+    mut input_reader: EventReader<HackInputEvent>,
+    mut print_to_console: EventWriter<PrintToConsoleEvent>,
+    mut data: ResMut<ConsoleData>,
+    mut collected_chars: ResMut<CollectedChars>,
+    mut game_state: ResMut<State<GameStates>>,
+    mut terminal_query: Query<&mut TerminalComponent>,
+    active_terminal: Res<ActiveTerminal>,
+) {
+    for HackInputEvent(input) in input_reader.iter() {
+        let input = input.trim().to_lowercase();
+
+        if let Some(session) = data.hack.as_mut() {
+            print_to_console.send(PrintToConsoleEvent(format!("[dim]# {}[/]", escape(&input))));
+
+            if input == "abort" {
+                data.hack = None;
+                fail_hack(
+                    &mut commands,
+                    &mut print_to_console,
+                    &active_terminal,
+                    Some(&mut game_state),
+                );
+                return;
+            }
+
+            let (solved, hint) = session.guess(&input);
+
+            if solved {
+                let reward = session.reward.clone();
+                data.hack = None;
+
+                // a successful hack uses an activation of the terminal
+                if let Some(mut terminal) = active_terminal
+                    .0
+                    .and_then(|entity| terminal_query.get_mut(entity).ok())
+                {
+                    terminal.activations_left -= 1;
+                }
+
+                for ch in reward.iter() {
+                    collected_chars.push(*ch);
+                }
+                print_to_console.send(PrintToConsoleEvent(format!(
+                    "[yellow]Access granted.[/] Recovered letters: [b]{}[/]",
+                    reward.iter().collect::<String>()
+                )));
+            } else if session.attempts_left == 0 {
+                data.hack = None;
+                fail_hack(
+                    &mut commands,
+                    &mut print_to_console,
+                    &active_terminal,
+                    Some(&mut game_state),
+                );
+            } else {
+                print_to_console.send(PrintToConsoleEvent(hint));
+            }
+        }
+    }
+}
+
+pub fn update_hack_timer(
+    mut commands: Commands,
+    mut print_to_console: EventWriter<PrintToConsoleEvent>,
+    mut data: ResMut<ConsoleData>,
+    mut game_state: ResMut<State<GameStates>>,
+    active_terminal: Res<ActiveTerminal>,
+    time: Res<Time>,
+) {
+    if let Some(session) = data.hack.as_mut() {
+        session.timer.tick(time.delta());
+
+        if session.timer.finished() {
+            data.hack = None;
+            print_to_console.send(PrintToConsoleEvent("[red]Time is up![/]".to_string()));
+            fail_hack(
+                &mut commands,
+                &mut print_to_console,
+                &active_terminal,
+                Some(&mut game_state),
+            );
+        }
+    }
+}
+
+// leaving the terminal during a hack counts as a failure
+pub fn abort_hack_on_exit(
+    mut commands: Commands,
+    mut print_to_console: EventWriter<PrintToConsoleEvent>,
+    mut data: ResMut<ConsoleData>,
+    active_terminal: Res<ActiveTerminal>,
+) {
+    // the console is already closing, no state to pop
+    if data.hack.take().is_some() {
+        fail_hack(&mut commands, &mut print_to_console, &active_terminal, None);
+    }
+}
+
+fn fail_hack(
+    commands: &mut Commands,
+    print_to_console: &mut EventWriter<PrintToConsoleEvent>,
+    active_terminal: &ActiveTerminal,
+    game_state: Option<&mut State<GameStates>>,
+) {
+    print_to_console.send(PrintToConsoleEvent(format!(
+        "[red]Intrusion detected! Terminal locked for {} seconds. Connection terminated.[/]",
+        LOCKOUT_TIME
+    )));
+
+    if let Some(terminal) = active_terminal.0 {
+        commands
+            .entity(terminal)
+            .insert(TerminalLockout(Timer::from_seconds(LOCKOUT_TIME, false)));
+    }
+
+    // kick the player out of the console like a failed cheat
+    if let Some(game_state) = game_state {
+        game_state.pop().unwrap();
+    }
+}
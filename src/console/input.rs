@@ -1,11 +1,12 @@
 use bevy::{input::keyboard::KeyboardInput, prelude::*};
 
-use super::event::SendCommandEvent;
+use super::{event::SendCommandEvent, hack::HackInputEvent};
 
 pub fn handle_input_keys(
     mut data: ResMut<super::ConsoleData>,
     mut evr_keys: EventReader<KeyboardInput>,
     mut send_command: EventWriter<SendCommandEvent>,
+    mut send_hack_input: EventWriter<HackInputEvent>,
     keyboard_input: Res<Input<KeyCode>>,
 ) {
     // don't do anything if control key is pressed
@@ -99,6 +100,13 @@ pub fn handle_input_keys(
                     }
 
                     KeyCode::Return | KeyCode::NumpadEnter => {
+                        // the hacking minigame takes over the input
+                        if data.hack.is_some() {
+                            send_hack_input.send(HackInputEvent(data.input.clone()));
+                            data.input.clear();
+                            continue;
+                        }
+
                         // sending the command
                         let command = data.input.clone();
                         send_command.send(SendCommandEvent(command.clone()));
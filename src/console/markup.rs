@@ -0,0 +1,169 @@
+use bevy::prelude::*;
+
+// Light markup used by the console output, for example "[red]error[/]".
+// Tags can be nested, "[/]" closes the last opened tag and "[[" prints a
+// literal '['. Unknown tags are printed as they are.
+
+pub const DEFAULT_COLOR: Color = Color::rgb(76. / 255., 207. / 255., 76. / 255.);
+
+fn tag_color(tag: &str) -> Option<Color> {
+    match tag {
+        "b" => Some(Color::rgb(180. / 255., 1.0, 180. / 255.)),
+        "dim" => Some(Color::rgb(40. / 255., 110. / 255., 40. / 255.)),
+        "red" => Some(Color::rgb(230. / 255., 60. / 255., 60. / 255.)),
+        "yellow" => Some(Color::rgb(240. / 255., 220. / 255., 80. / 255.)),
+        "green" => Some(DEFAULT_COLOR),
+        _ => None,
+    }
+}
+
+/// Escapes a text so that it is displayed as is
+pub fn escape(text: &str) -> String {
+    text.replace('[', "[[")
+}
+
+/// Splits a marked up text into sections with the matching colors
+pub fn parse_markup(text: &str, font: &Handle<Font>, font_size: f32) -> Vec<TextSection> {
+    let mut sections = Vec::new();
+    let mut colors = vec![DEFAULT_COLOR];
+    let mut current = String::new();
+    let mut rest = text;
+
+    while let Some(start) = rest.find('[') {
+        current.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        if rest.starts_with("[[") {
+            current.push('[');
+            rest = &rest[2..];
+            continue;
+        }
+
+        let tag = match rest.find(']') {
+            Some(end) => &rest[1..end],
+            None => "",
+        };
+
+        if tag == "/" || tag_color(tag).is_some() {
+            // close the current section before switching color
+            push_section(
+                &mut sections,
+                &mut current,
+                *colors.last().unwrap(),
+                font,
+                font_size,
+            );
+
+            if tag == "/" {
+                if colors.len() > 1 {
+                    colors.pop();
+                }
+            } else {
+                colors.push(tag_color(tag).unwrap());
+            }
+            rest = &rest[tag.len() + 2..];
+        } else {
+            current.push('[');
+            rest = &rest[1..];
+        }
+    }
+    current.push_str(rest);
+    push_section(
+        &mut sections,
+        &mut current,
+        *colors.last().unwrap(),
+        font,
+        font_size,
+    );
+
+    sections
+}
+
+fn push_section(
+    sections: &mut Vec<TextSection>,
+    value: &mut String,
+    color: Color,
+    font: &Handle<Font>,
+    font_size: f32,
+) {
+    if value.is_empty() {
+        return;
+    }
+
+    sections.push(TextSection {
+        value: std::mem::take(value),
+        style: TextStyle {
+            font: font.clone(),
+            font_size,
+            color,
+        },
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(text: &str) -> Vec<(String, Color)> {
+        parse_markup(text, &Handle::default(), 20.0)
+            .into_iter()
+            .map(|section| (section.value, section.style.color))
+            .collect()
+    }
+
+    #[test]
+    fn colors_the_tagged_text() {
+        assert_eq!(
+            parse("ok [red]error[/] done"),
+            vec![
+                ("ok ".to_string(), DEFAULT_COLOR),
+                ("error".to_string(), tag_color("red").unwrap()),
+                (" done".to_string(), DEFAULT_COLOR),
+            ]
+        );
+    }
+
+    #[test]
+    fn unclosed_tag_colors_the_rest() {
+        assert_eq!(
+            parse("[red]no closing tag"),
+            vec![("no closing tag".to_string(), tag_color("red").unwrap())]
+        );
+    }
+
+    #[test]
+    fn tag_without_bracket_is_printed() {
+        assert_eq!(
+            parse("[red no bracket"),
+            vec![("[red no bracket".to_string(), DEFAULT_COLOR)]
+        );
+    }
+
+    #[test]
+    fn extra_closing_tag_keeps_default_color() {
+        assert_eq!(
+            parse("[/]text[/]"),
+            vec![("text".to_string(), DEFAULT_COLOR)]
+        );
+    }
+
+    #[test]
+    fn nested_tags_restore_outer_color() {
+        assert_eq!(
+            parse("[yellow]a[red]b[/]c[/]"),
+            vec![
+                ("a".to_string(), tag_color("yellow").unwrap()),
+                ("b".to_string(), tag_color("red").unwrap()),
+                ("c".to_string(), tag_color("yellow").unwrap()),
+            ]
+        );
+    }
+
+    #[test]
+    fn escaped_and_unknown_tags_are_printed() {
+        assert_eq!(
+            parse(&format!("{} [blue]", escape("[red]"))),
+            vec![("[red] [blue]".to_string(), DEFAULT_COLOR)]
+        );
+    }
+}
@@ -1,22 +1,30 @@
+use std::time::Duration;
+
 use bevy::prelude::*;
 use bevy_kira_audio::{Audio, AudioChannel};
 use bevy_loading::prelude::*;
 
 use self::{
     event::{PrintToConsoleEvent, SendCommandEvent},
+    hack::HackInputEvent,
     loading_screen::LoadingScreenPlugin,
 };
-use crate::runner::Player;
+use crate::controls::InputAction;
+use crate::runner::{Checkpoint, Player};
 use crate::states::GameStates;
+use crate::toast::ShowToast;
 use crate::{
     cheat_codes::CheatCodeKind,
-    interactables::{InteractableComponent, InteractableType},
+    interactables::{InteractableComponent, InteractableType, TerminalLockout},
 };
 
 mod commands;
 mod event;
+mod hack;
 mod input;
 mod loading_screen;
+pub mod markup;
+mod session;
 mod ui;
 mod utils;
 
@@ -51,11 +59,20 @@ impl Plugin for ConsolePlugin {
             history_index: 0,
             history: Vec::new(),
             lines: utils::welcome_lines(),
+            logs: Vec::new(),
+            files: Vec::new(),
+            hack: None,
         })
+        .insert_resource(ActiveTerminal(None))
         .add_event::<PrintToConsoleEvent>()
         .add_event::<SendCommandEvent>()
+        .add_event::<HackInputEvent>()
         // on enter
-        .add_system_set(SystemSet::on_enter(GameStates::Console).with_system(ui::build_ui))
+        .add_system_set(
+            SystemSet::on_enter(GameStates::Console)
+                .with_system(ui::build_ui)
+                .with_system(session::load_terminal_session),
+        )
         // on update
         .add_system_set(
             SystemSet::on_update(GameStates::Console)
@@ -68,17 +85,24 @@ impl Plugin for ConsolePlugin {
                 .with_system(update_input_area)
                 .with_system(update_lines_area)
                 .with_system(event::add_message_events_to_console)
+                .with_system(session::update_terminal_info)
                 .label("update_ui"),
         )
         .add_system_set(
             SystemSet::on_update(GameStates::Console)
                 .with_system(input::handle_input_keys)
                 .with_system(commands::command_handler)
+                .with_system(hack::handle_hack_input)
+                .with_system(hack::update_hack_timer)
                 .after("update_ui"),
         )
         // on exit
         .add_system_set(
-            SystemSet::on_exit(GameStates::Console).with_system(destroy_console_state_entities),
+            SystemSet::on_exit(GameStates::Console)
+                .with_system(destroy_console_state_entities)
+                .with_system(hack::abort_hack_on_exit.label("abort_hack"))
+                // save the console once the running hack is aborted
+                .with_system(session::save_terminal_session.after("abort_hack")),
         );
     }
 }
@@ -107,8 +131,16 @@ pub struct ConsoleData {
     history_index: usize,
     history: Vec<String>,
     lines: Vec<String>,
+    // content of the connected terminal
+    logs: Vec<String>,
+    files: Vec<(String, String)>,
+    // running hacking minigame, takes over the command input
+    hack: Option<hack::HackSession>,
 }
 
+/// Terminal the console is currently connected to
+pub struct ActiveTerminal(pub Option<Entity>);
+
 fn destroy_console_state_entities(
     mut commands: Commands,
     entities_query: Query<Entity, With<ConsoleStateEntity>>,
@@ -121,27 +153,35 @@ fn destroy_console_state_entities(
 }
 
 fn close_console_handler(
-    mut keyboard: ResMut<Input<KeyCode>>,
+    mut actions: ResMut<Input<InputAction>>,
     mut game_state: ResMut<State<GameStates>>,
 ) {
-    if keyboard.just_pressed(KeyCode::Escape) {
+    if actions.just_pressed(InputAction::Pause) {
         game_state.pop().unwrap();
-        keyboard.reset(KeyCode::Escape);
+        actions.reset(InputAction::Pause);
     }
 }
 
 fn open_console_handler(
-    mut keyboard: ResMut<Input<KeyCode>>,
+    mut actions: ResMut<Input<InputAction>>,
     mut game_state: ResMut<State<GameStates>>,
     player_query: Query<&Transform, With<Player>>,
-    interactable_query: Query<(&InteractableComponent, &Transform)>,
+    interactable_query: Query<(
+        Entity,
+        &InteractableComponent,
+        &Transform,
+        Option<&TerminalLockout>,
+    )>,
+    mut active_terminal: ResMut<ActiveTerminal>,
+    mut checkpoint: ResMut<Checkpoint>,
+    mut toast_writer: EventWriter<ShowToast>,
     asset_server: Res<AssetServer>,
     audio: Res<Audio>,
 ) {
-    if keyboard.just_released(KeyCode::E) {
+    if actions.just_released(InputAction::Interact) {
         // Only open the terminal when in range
         if let Some(player) = player_query.iter().next() {
-            for (interactable, transform) in interactable_query.iter() {
+            for (entity, interactable, transform, lockout) in interactable_query.iter() {
                 match interactable.interactable_type {
                     InteractableType::Terminal => {
                         let distance_x = player.translation.x - transform.translation.x;
@@ -153,8 +193,32 @@ fn open_console_handler(
                             && distance_y <= range
                             && distance_y >= -range
                         {
+                            if let Some(lockout) = lockout {
+                                let remaining = lockout.0.duration() - lockout.0.elapsed();
+                                toast_writer.send(ShowToast {
+                                    value: format!(
+                                        "Terminal locked for {}s",
+                                        remaining.as_secs() + 1
+                                    ),
+                                    duration: Duration::from_secs(2),
+                                });
+                                actions.reset(InputAction::Interact);
+                                continue;
+                            }
+
+                            // the terminal becomes the respawn point
+                            let terminal_position = transform.translation.truncate();
+                            if checkpoint.position != Some(terminal_position) {
+                                checkpoint.position = Some(terminal_position);
+                                toast_writer.send(ShowToast {
+                                    value: "Checkpoint saved".to_string(),
+                                    duration: Duration::from_secs(2),
+                                });
+                            }
+
+                            active_terminal.0 = Some(entity);
                             game_state.push(GameStates::ConsoleLoading).unwrap();
-                            keyboard.reset(KeyCode::E);
+                            actions.reset(InputAction::Interact);
                             let audio_channel = AudioChannel::new("sfx-channel".to_owned());
                             audio.set_volume_in_channel(10.0, &audio_channel);
                             audio.play_in_channel(asset_server.load("crt.ogg"), &audio_channel);
@@ -172,15 +236,15 @@ pub fn update_lines_area(
     console_assets: Res<ConsoleAssets>,
     mut lines_area_query: Query<&mut Text, With<ui::LinesArea>>,
 ) {
-    let sections_text = data.lines.join("\n");
-    let sections = vec![TextSection {
-        value: sections_text,
-        style: TextStyle {
-            font: console_assets.crt_font.clone(),
-            font_size: 16.,
-            color: Color::rgba_u8(76, 207, 76, 255),
-        },
-    }];
+    // each message is parsed on its own so that an unclosed tag doesn't leak
+    let mut sections = Vec::new();
+    for (i, line) in data.lines.iter().enumerate() {
+        let mut line = line.clone();
+        if i < data.lines.len() - 1 {
+            line.push('\n');
+        }
+        sections.extend(markup::parse_markup(&line, &console_assets.crt_font, 16.));
+    }
 
     let mut text = lines_area_query.single_mut();
     text.sections = sections;
@@ -200,7 +264,10 @@ pub fn update_input_area(
         state.input = trimmed_command;
     }
 
-    let mut to_show = String::from("user@hacked_pc > ");
+    let mut to_show = match &state.hack {
+        Some(session) => format!("breach [{}s] # ", session.seconds_left()),
+        None => String::from("user@hacked_pc > "),
+    };
     to_show.push_str(&state.input);
 
     if (time.seconds_since_startup() * 3.0) as u64 % 2 == 0 {
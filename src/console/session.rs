@@ -0,0 +1,90 @@
+use bevy::prelude::*;
+
+use super::{ui::TerminalInfoText, utils, ActiveTerminal, ConsoleData};
+use crate::interactables::{TerminalComponent, TERMINAL_MAX_FAILED_ATTEMPTS};
+
+const LOGS_PER_TERMINAL: usize = 2;
+const FILES_PER_TERMINAL: usize = 2;
+
+/// Console content saved on a terminal between two connections
+#[derive(Component)]
+pub struct ConsoleSession {
+    lines: Vec<String>,
+    history: Vec<String>,
+    logs: Vec<String>,
+    files: Vec<(String, String)>,
+}
+
+impl ConsoleSession {
+    fn new() -> Self {
+        Self {
+            lines: utils::welcome_lines(),
+            history: Vec::new(),
+            logs: utils::random_logs(LOGS_PER_TERMINAL),
+            files: utils::random_files(FILES_PER_TERMINAL),
+        }
+    }
+}
+
+pub fn load_terminal_session(
+    mut data: ResMut<ConsoleData>,
+    active_terminal: Res<ActiveTerminal>,
+    session_query: Query<&ConsoleSession>,
+) {
+    let session = active_terminal
+        .0
+        .and_then(|entity| session_query.get(entity).ok());
+
+    // first connection to this terminal
+    let new_session;
+    let session = match session {
+        Some(session) => session,
+        None => {
+            new_session = ConsoleSession::new();
+            &new_session
+        }
+    };
+
+    data.input.clear();
+    data.lines = session.lines.clone();
+    data.history = session.history.clone();
+    data.history_index = data.history.len();
+    data.logs = session.logs.clone();
+    data.files = session.files.clone();
+}
+
+pub fn save_terminal_session(
+    mut commands: Commands,
+    data: Res<ConsoleData>,
+    active_terminal: Res<ActiveTerminal>,
+) {
+    if let Some(entity) = active_terminal.0 {
+        commands.entity(entity).insert(ConsoleSession {
+            lines: data.lines.clone(),
+            history: data.history.clone(),
+            logs: data.logs.clone(),
+            files: data.files.clone(),
+        });
+    }
+}
+
+pub fn update_terminal_info(
+    active_terminal: Res<ActiveTerminal>,
+    terminal_query: Query<(&TerminalComponent, &Transform)>,
+    mut text_query: Query<&mut Text, With<TerminalInfoText>>,
+) {
+    if let Some(entity) = active_terminal.0 {
+        if let Ok((terminal, transform)) = terminal_query.get(entity) {
+            for mut text in text_query.iter_mut() {
+                text.sections[0].value = format!(
+                    "Connected to {} @ {:.0}m | activations left: {} | failed attempts: {}/{}",
+                    terminal.display_id(),
+                    transform.translation.x / 100.,
+                    terminal.activations_left,
+                    terminal.failed_attempts,
+                    TERMINAL_MAX_FAILED_ATTEMPTS
+                );
+            }
+        }
+    }
+}
@@ -15,6 +15,9 @@ pub struct LinesArea;
 #[derive(Component)]
 pub struct CommandInput;
 
+#[derive(Component)]
+pub struct TerminalInfoText;
+
 // Debug function to hide the foreground
 pub fn hide_foreground(
     keyboard: Res<Input<KeyCode>>,
@@ -129,6 +132,34 @@ pub fn build_ui(
         ..Default::default()
     };
 
+    // connected terminal, above the console
+    let terminal_info_container = NodeBundle {
+        style: Style {
+            position_type: PositionType::Absolute,
+            size: Size::new(Val::Px(current_window.width() / 2.0), Val::Px(24.0)),
+            position: Rect {
+                left: Val::Px(current_window.width() / 4.0),
+                top: Val::Px(current_window.height() / 6.0 - 30.0),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        color: Color::rgba_u8(0, 0, 0, 0).into(),
+        ..Default::default()
+    };
+    let terminal_info_component = TextBundle {
+        text: Text::with_section(
+            "",
+            TextStyle {
+                font: console_assets.crt_font.clone(),
+                font_size: 20.,
+                color: Color::rgba_u8(102, 255, 102, 255),
+            },
+            Default::default(),
+        ),
+        ..Default::default()
+    };
+
     // Displaying cheat codes and collected letter in margins.
     // TODO: REFACTOR!! Duplicated code from second_page.rs
     let cheat_codes_content_container = NodeBundle {
@@ -285,6 +316,14 @@ pub fn build_ui(
                             parent.spawn_bundle(command_component).insert(CommandInput);
                         });
                 });
+            // connected terminal
+            parent
+                .spawn_bundle(terminal_info_container)
+                .with_children(|parent| {
+                    parent
+                        .spawn_bundle(terminal_info_component)
+                        .insert(TerminalInfoText);
+                });
             // foreground
             parent
                 .spawn_bundle(foreground_component)
@@ -21,6 +21,9 @@ pub fn welcome_lines() -> Vec<String> {
         "    - help: show the available commands",
         "    - cheat <code>: enable a cheat code to activate an ability",
         "    - log: display a log entry",
+        "    - ls: list the files of this terminal",
+        "    - cat <file>: display the content of a file",
+        "    - hack: breach the terminal to recover letters",
         "    - clear: clear the entire display",
         "    - exit: exit this terminal to go back to boring reality",
         " ",
@@ -44,6 +47,9 @@ pub fn display_help() -> String {
         "    - help: show the available commands",
         "    - cheat <code>: enable a cheat code for this world",
         "    - log: display a log entry",
+        "    - ls: list the files of this terminal",
+        "    - cat <file>: display the content of a file",
+        "    - hack: breach the terminal to recover letters",
         "    - clear: clear the entire display",
         "    - exit: exit this terminal to go back to boring reality",
         " ",
@@ -56,7 +62,8 @@ pub fn display_help() -> String {
         .join("\n")
 }
 
-pub fn display_random_log() -> String {
+/// Picks the logs stored on a terminal
+pub fn random_logs(amount: usize) -> Vec<String> {
     let logs: Vec<Vec<&str>> = vec![
         vec![
             "3/4/2077:",
@@ -86,10 +93,53 @@ pub fn display_random_log() -> String {
         ],
     ];
 
-    let random_log = logs.choose(&mut rand::thread_rng()).unwrap();
-    random_log
-        .iter()
-        .map(|line| line.to_string())
-        .collect::<Vec<String>>()
-        .join("\n")
+    logs.choose_multiple(&mut rand::thread_rng(), amount)
+        .map(|log| {
+            log.iter()
+                .map(|line| line.to_string())
+                .collect::<Vec<String>>()
+                .join("\n")
+        })
+        .collect()
+}
+
+/// Picks the files stored on a terminal, as (name, content)
+pub fn random_files(amount: usize) -> Vec<(String, String)> {
+    let files: Vec<(&str, Vec<&str>)> = vec![
+        (
+            "readme.txt",
+            vec![
+                "This terminal is the property of the World Government.",
+                "Unauthorized use will be reported to President Bozo.",
+            ],
+        ),
+        (
+            "todo.txt",
+            vec![
+                "- renew jumping license (again)",
+                "- find out who keeps leaving letters on the street",
+                "- do NOT type anything after \"cheat\"",
+            ],
+        ),
+        (
+            "memo.txt",
+            vec![
+                "To all operators:",
+                "Terminals are limited to a few activations each. Do not waste them.",
+                "Repeated failed activations will lock the terminal.",
+            ],
+        ),
+        (
+            "passwd",
+            vec![
+                "root:x:0:0:bozo:/root:/bin/bozosh",
+                "operator:x:1000:1000::/home/operator:/bin/bozosh",
+            ],
+        ),
+    ];
+
+    files
+        .choose_multiple(&mut rand::thread_rng(), amount)
+        .map(|(name, lines)| (name.to_string(), lines.join("\n")))
+        .collect()
 }
@@ -0,0 +1,169 @@
+use std::collections::{HashMap, HashSet};
+
+use bevy::input::InputSystem;
+use bevy::prelude::*;
+use ron::de::from_bytes;
+use serde::Deserialize;
+
+use crate::platforms::data::{self, DataResource};
+
+pub struct ControlsPlugin;
+
+impl Plugin for ControlsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(
+            from_bytes::<InputBindings>(include_bytes!("../assets/data/controls.ron")).unwrap(),
+        )
+        .init_resource::<Input<InputAction>>()
+        .init_resource::<ActiveActions>()
+        .add_startup_system(data::load_data::<InputBindings>)
+        .add_system(data::reload_data::<InputBindings>)
+        .add_system_to_stage(CoreStage::PreUpdate, update_actions.after(InputSystem));
+    }
+}
+
+/// What the player can do, read with `Res<Input<InputAction>>` instead of the raw inputs
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum InputAction {
+    MoveRight,
+    MoveLeft,
+    Jump,
+    Dash,
+    Interact,
+    Journal,
+    Pause,
+    Crouch,
+    // ends the run on the spot
+    GiveUp,
+}
+
+/// Input triggering an action
+#[derive(Deserialize, Debug, Clone, Copy)]
+pub enum Binding {
+    Key(KeyCode),
+    Button(GamepadButtonType),
+    /// Active past the threshold, a negative threshold for the negative side of the axis
+    Axis(GamepadAxisType, f32),
+}
+
+impl Binding {
+    fn is_gamepad(&self) -> bool {
+        !matches!(self, Binding::Key(_))
+    }
+
+    /// Name shown in the help texts
+    fn name(&self) -> String {
+        match self {
+            Binding::Key(key) => format!("{:?}", key).to_uppercase(),
+            Binding::Button(button) => format!("{:?}", button),
+            Binding::Axis(axis, threshold) if *threshold < 0.0 => format!("{:?}-", axis),
+            Binding::Axis(axis, _) => format!("{:?}+", axis),
+        }
+    }
+}
+
+/// Bindings of every action, defined in `assets/data/controls.ron`
+#[derive(Deserialize)]
+pub struct InputBindings {
+    pub bindings: HashMap<InputAction, Vec<Binding>>,
+    // whether the last input came from a gamepad, the help texts follow it
+    #[serde(skip)]
+    pub using_gamepad: bool,
+}
+
+impl DataResource for InputBindings {
+    const PATH: &'static str = "data/controls.ron";
+
+    fn reload(&mut self, new: Self) {
+        self.bindings = new.bindings;
+    }
+}
+
+impl InputBindings {
+    /// Name of the binding of an action on the device in use
+    pub fn describe(&self, action: InputAction) -> String {
+        let bindings = match self.bindings.get(&action) {
+            Some(bindings) => bindings,
+            None => return "?".to_string(),
+        };
+
+        bindings
+            .iter()
+            .find(|binding| binding.is_gamepad() == self.using_gamepad)
+            .or_else(|| bindings.first())
+            .map_or("?".to_string(), Binding::name)
+    }
+
+    /// Replaces the action names between braces, as in `Press {Jump} to jump`
+    pub fn fill(&self, text: &str) -> String {
+        [
+            InputAction::MoveRight,
+            InputAction::MoveLeft,
+            InputAction::Jump,
+            InputAction::Dash,
+            InputAction::Interact,
+            InputAction::Journal,
+            InputAction::Pause,
+            InputAction::Crouch,
+            InputAction::GiveUp,
+        ]
+        .iter()
+        .fold(text.to_string(), |text, action| {
+            text.replace(&format!("{{{:?}}}", action), &self.describe(*action))
+        })
+    }
+}
+
+/// Actions held during the last frame
+#[derive(Default)]
+struct ActiveActions(HashSet<InputAction>);
+
+fn update_actions(
+    keyboard: Res<Input<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+    gamepad_axes: Res<Axis<GamepadAxis>>,
+    mut bindings: ResMut<InputBindings>,
+    mut active_actions: ResMut<ActiveActions>,
+    mut actions: ResMut<Input<InputAction>>,
+) {
+    actions.clear();
+
+    let is_active = |binding: &Binding| match *binding {
+        Binding::Key(key) => keyboard.pressed(key),
+        Binding::Button(button) => gamepads
+            .iter()
+            .any(|gamepad| gamepad_buttons.pressed(GamepadButton(*gamepad, button))),
+        Binding::Axis(axis, threshold) => gamepads.iter().any(|gamepad| {
+            let value = gamepad_axes.get(GamepadAxis(*gamepad, axis)).unwrap_or(0.0);
+            if threshold < 0.0 {
+                value <= threshold
+            } else {
+                value >= threshold
+            }
+        }),
+    };
+
+    let mut using_gamepad = None;
+    let mut active = HashSet::new();
+    for (action, action_bindings) in bindings.bindings.iter() {
+        for binding in action_bindings.iter().filter(|binding| is_active(binding)) {
+            active.insert(*action);
+            using_gamepad = Some(binding.is_gamepad());
+        }
+    }
+    if let Some(using_gamepad) = using_gamepad {
+        if bindings.using_gamepad != using_gamepad {
+            bindings.using_gamepad = using_gamepad;
+        }
+    }
+
+    // only the changes are applied, so that a reset action stays released until pressed again
+    for action in active.difference(&active_actions.0) {
+        actions.press(*action);
+    }
+    for action in active_actions.0.difference(&active) {
+        actions.release(*action);
+    }
+    active_actions.0 = active;
+}
@@ -0,0 +1,234 @@
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+use serde::Deserialize;
+
+use super::Enemy;
+use crate::platforms::platform::Platform;
+use crate::runner::Player;
+
+// dead enemies are despawned after this many seconds
+const DEATH_DURATION: f32 = 0.5;
+// height of the state label above the enemy, in pixels
+#[cfg(debug_assertions)]
+const LABEL_HEIGHT: f32 = 40.0;
+
+/// Perception of an enemy kind, defined in `assets/data/enemies.ron`
+#[derive(Deserialize, Debug, Clone)]
+pub struct EnemyAiData {
+    // in pixels
+    pub sight_range: f32,
+    pub attack_range: f32,
+    // whether the platforms between the enemy and the player hide the player
+    #[serde(default)]
+    pub needs_line_of_sight: bool,
+    // in seconds, spent alert after losing sight of the player
+    pub alert_time: f32,
+    // in seconds
+    pub stun_time: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnemyState {
+    Idle,
+    Patrol,
+    Alert,
+    Attack,
+    Stunned,
+    Dead,
+}
+
+#[derive(Debug, Component)]
+pub struct EnemyAi {
+    pub state: EnemyState,
+    // state of the enemy while the player is out of sight
+    resting_state: EnemyState,
+    data: EnemyAiData,
+    // in seconds
+    state_time: f32,
+    since_seen: f32,
+    // from the enemy to the player, in pixels
+    pub to_player: Vec2,
+}
+
+impl EnemyAi {
+    pub fn new(data: EnemyAiData, resting_state: EnemyState) -> Self {
+        Self {
+            state: resting_state,
+            resting_state,
+            data,
+            state_time: 0.0,
+            since_seen: f32::INFINITY,
+            to_player: Vec2::ZERO,
+        }
+    }
+
+    fn set_state(&mut self, state: EnemyState) {
+        if self.state != state {
+            self.state = state;
+            self.state_time = 0.0;
+        }
+    }
+
+    /// Stops the enemy for the stun time of its kind
+    pub fn stun(&mut self) {
+        if self.state != EnemyState::Dead {
+            self.set_state(EnemyState::Stunned);
+        }
+    }
+}
+
+/// Whether no platform stands between two points, in pixels
+fn line_of_sight(
+    from: Vec2,
+    to: Vec2,
+    rapier_config: &RapierConfiguration,
+    query_pipeline: &QueryPipeline,
+    collider_query: &QueryPipelineColliderComponentsQuery,
+    platform_query: &Query<(), With<Platform>>,
+) -> bool {
+    let collider_set = QueryPipelineColliderComponentsSet(collider_query);
+    let ray = Ray::new(
+        (from / rapier_config.scale).into(),
+        ((to - from) / rapier_config.scale).into(),
+    );
+    let is_platform = |handle: ColliderHandle| platform_query.get(handle.entity()).is_ok();
+
+    query_pipeline
+        .cast_ray(
+            &collider_set,
+            &ray,
+            1.0,
+            true,
+            InteractionGroups::all(),
+            Some(&is_platform),
+        )
+        .is_none()
+}
+
+pub fn update_enemy_ai(
+    time: Res<Time>,
+    rapier_config: Res<RapierConfiguration>,
+    query_pipeline: Res<QueryPipeline>,
+    collider_query: QueryPipelineColliderComponentsQuery,
+    platform_query: Query<(), With<Platform>>,
+    player_query: Query<&Transform, With<Player>>,
+    mut enemy_query: Query<(&Enemy, &Transform, &mut EnemyAi)>,
+) {
+    let player_position = match player_query.iter().next() {
+        Some(transform) => transform.translation.truncate(),
+        None => return,
+    };
+
+    for (enemy, transform, mut ai) in enemy_query.iter_mut() {
+        ai.state_time += time.delta_seconds();
+        ai.since_seen += time.delta_seconds();
+
+        if enemy.health == 0 {
+            ai.set_state(EnemyState::Dead);
+        }
+        if ai.state == EnemyState::Dead
+            || (ai.state == EnemyState::Stunned && ai.state_time < ai.data.stun_time)
+        {
+            continue;
+        }
+
+        let position = transform.translation.truncate();
+        ai.to_player = player_position - position;
+        let distance = ai.to_player.length();
+
+        let sees_player = distance <= ai.data.sight_range
+            && (!ai.data.needs_line_of_sight
+                || line_of_sight(
+                    position,
+                    player_position,
+                    &rapier_config,
+                    &query_pipeline,
+                    &collider_query,
+                    &platform_query,
+                ));
+        if sees_player {
+            ai.since_seen = 0.0;
+        }
+
+        let state = if sees_player && distance <= ai.data.attack_range {
+            EnemyState::Attack
+        } else if ai.since_seen < ai.data.alert_time {
+            EnemyState::Alert
+        } else {
+            ai.resting_state
+        };
+        ai.set_state(state);
+    }
+}
+
+/// Fades the dead enemies out before despawning them
+pub fn despawn_dead_enemies(
+    mut commands: Commands,
+    mut enemy_query: Query<(Entity, &EnemyAi, Option<&mut Sprite>)>,
+) {
+    for (entity, ai, sprite) in enemy_query.iter_mut() {
+        if ai.state != EnemyState::Dead {
+            continue;
+        }
+
+        if ai.state_time >= DEATH_DURATION {
+            commands.entity(entity).despawn_recursive();
+        } else if let Some(mut sprite) = sprite {
+            sprite.color.set_a(1.0 - ai.state_time / DEATH_DURATION);
+        }
+    }
+}
+
+/// Debug label showing the state of an enemy
+#[cfg(debug_assertions)]
+#[derive(Component)]
+pub struct EnemyStateLabel;
+
+#[cfg(debug_assertions)]
+pub fn add_state_labels(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    enemy_query: Query<Entity, Added<EnemyAi>>,
+) {
+    for entity in enemy_query.iter() {
+        commands.entity(entity).with_children(|parent| {
+            parent
+                .spawn_bundle(Text2dBundle {
+                    text: Text::with_section(
+                        "",
+                        TextStyle {
+                            font: asset_server.load("fonts/VT323-Regular.ttf"),
+                            font_size: 18.0,
+                            color: Color::YELLOW,
+                        },
+                        TextAlignment {
+                            vertical: VerticalAlign::Center,
+                            horizontal: HorizontalAlign::Center,
+                        },
+                    ),
+                    transform: Transform::from_xyz(0.0, LABEL_HEIGHT, 10.0),
+                    ..Default::default()
+                })
+                .insert(EnemyStateLabel);
+        });
+    }
+}
+
+#[cfg(debug_assertions)]
+pub fn update_state_labels(
+    enemy_query: Query<(&EnemyAi, &Transform, &Children), Without<EnemyStateLabel>>,
+    mut label_query: Query<(&mut Text, &mut Transform), With<EnemyStateLabel>>,
+) {
+    for (ai, enemy_transform, children) in enemy_query.iter() {
+        for child in children.iter() {
+            if let Ok((mut text, mut transform)) = label_query.get_mut(*child) {
+                text.sections[0].value = format!("{:?}", ai.state);
+
+                // stays upright above the rotating enemies
+                let rotation = enemy_transform.rotation.inverse();
+                transform.rotation = rotation;
+                transform.translation = rotation * Vec3::new(0.0, LABEL_HEIGHT, 10.0);
+            }
+        }
+    }
+}
@@ -0,0 +1,110 @@
+use bevy::prelude::*;
+use rand::Rng;
+use serde::Deserialize;
+
+use super::EnemyKindsResource;
+use crate::interactables::spawn_char;
+use crate::letter_director::LetterDirector;
+use crate::platforms::chunk::ChunkRoot;
+use crate::stats::EnemyKilledEvent;
+
+// in seconds
+const POP_DURATION: f32 = 0.4;
+// in pixels, above the position of the enemy
+const POP_HEIGHT: f32 = 40.0;
+// scale of the letter sprites once popped
+const LETTER_SCALE: f32 = 2.0;
+
+/// Letter dropped by an enemy kind when killed, defined in `assets/data/enemies.ron`
+#[derive(Deserialize, Debug, Default, Clone)]
+pub struct DropTable {
+    // between 0 and 1
+    pub chance: f32,
+    // whether the letter is one of the target code that the player is missing
+    #[serde(default)]
+    pub favor_target: bool,
+}
+
+/// Letter dropped by a killed enemy
+#[derive(Debug, Component)]
+pub struct DroppedLetter {
+    pop_timer: Timer,
+    // in pixels
+    origin: Vec2,
+}
+
+pub fn drop_letters(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+    enemy_kinds: Res<EnemyKindsResource>,
+    mut letter_director: ResMut<LetterDirector>,
+    mut enemy_killed_events: EventReader<EnemyKilledEvent>,
+    chunk_query: Query<&ChunkRoot>,
+) {
+    for event in enemy_killed_events.iter() {
+        // bosses have their own loot
+        let drops = match enemy_kinds.kinds.get(&event.kind) {
+            Some(enemy_data) => &enemy_data.drops,
+            None => continue,
+        };
+
+        if !rand::thread_rng().gen_bool(drops.chance.clamp(0.0, 1.0) as f64) {
+            continue;
+        }
+
+        let letter = letter_director.pick_drop(drops.favor_target);
+        let entity = spawn_char(
+            &mut commands,
+            &asset_server,
+            &mut texture_atlases,
+            letter,
+            &event.position,
+        );
+        // grows from nothing
+        commands
+            .entity(entity)
+            .insert(Transform {
+                translation: event.position.extend(99.0),
+                scale: Vec3::ZERO,
+                ..Default::default()
+            })
+            .insert(DroppedLetter {
+                pop_timer: Timer::from_seconds(POP_DURATION, false),
+                origin: event.position,
+            });
+
+        // the chunk root stays at the origin, the letter keeps its world position
+        if let Some(chunk) = event.chunk.filter(|chunk| chunk_query.get(*chunk).is_ok()) {
+            commands.entity(chunk).push_children(&[entity]);
+        }
+    }
+}
+
+/// Letters grow out of the enemy and jump up before settling
+pub fn pop_letters(time: Res<Time>, mut letter_query: Query<(&mut DroppedLetter, &mut Transform)>) {
+    for (mut letter, mut transform) in letter_query.iter_mut() {
+        if letter.pop_timer.finished() {
+            continue;
+        }
+        letter.pop_timer.tick(time.delta());
+
+        let progress = letter.pop_timer.percent();
+        let height = POP_HEIGHT * (1.0 - (1.0 - progress).powi(2));
+        // overshoots a bit before settling
+        let scale = LETTER_SCALE * (progress + 0.5 * (progress * std::f32::consts::PI).sin());
+
+        transform.translation.x = letter.origin.x;
+        transform.translation.y = letter.origin.y + height;
+        transform.scale = Vec3::new(scale, scale, 1.0);
+    }
+}
+
+pub fn despawn_dropped_letters(
+    mut commands: Commands,
+    letter_query: Query<Entity, With<DroppedLetter>>,
+) {
+    for entity in letter_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -1,76 +1,228 @@
+use std::collections::HashMap;
+
+use crate::platforms::data::{self, DataResource};
 use crate::{physics::jump, states::GameStates};
 use bevy::prelude::*;
 use bevy_rapier2d::prelude::*;
-use serde::Deserialize;
+use ron::de::from_bytes;
+use serde::{Deserialize, Serialize};
+
+mod ai;
+mod drops;
+mod projectile;
+
+pub use self::ai::{EnemyAi, EnemyAiData, EnemyState};
+pub use self::drops::DropTable;
+pub use self::projectile::spawn_projectile;
+
+// speed of a patrolling enemy charging the player, relative to its patrol speed
+const CHARGE_FACTOR: f32 = 2.0;
 
 pub struct EnemiesPlugin;
 
 impl Plugin for EnemiesPlugin {
     fn build(&self, app: &mut App) {
+        app.insert_resource(
+            from_bytes::<EnemyKindsResource>(include_bytes!("../../assets/data/enemies.ron"))
+                .unwrap(),
+        )
+        .add_startup_system(data::load_data::<EnemyKindsResource>)
+        .add_system(data::reload_data::<EnemyKindsResource>)
+        .add_system_set(
+            SystemSet::on_update(GameStates::Main)
+                .with_system(ai::update_enemy_ai.label("enemy_ai"))
+                .with_system(ai::despawn_dead_enemies.after("enemy_ai"))
+                .with_system(hopper_enemy_behavior.after("enemy_ai"))
+                .with_system(patrol_enemy_behavior.after("enemy_ai"))
+                .with_system(turret_enemy_behavior.after("enemy_ai"))
+                .with_system(chaser_enemy_behavior.after("enemy_ai"))
+                .with_system(projectile::projectile_hit.after("player_state"))
+                .with_system(projectile::expire_projectiles)
+                .with_system(drops::drop_letters)
+                .with_system(drops::pop_letters),
+        )
+        .add_system_set(
+            SystemSet::on_exit(GameStates::Main)
+                .with_system(projectile::despawn_projectiles)
+                .with_system(drops::despawn_dropped_letters),
+        );
+
+        // state of every enemy above its head
+        #[cfg(debug_assertions)]
         app.add_system_set(
-            SystemSet::on_update(GameStates::Main).with_system(slime_enemy_behavior),
+            SystemSet::on_update(GameStates::Main)
+                .with_system(ai::add_state_labels)
+                .with_system(ai::update_state_labels.after("enemy_ai")),
         );
     }
 }
 
-#[derive(Deserialize, Debug, Hash, PartialEq, Eq, Clone)]
-pub enum EnemyKind {
-    Slime,
+/// Name of an enemy kind defined in `assets/data/enemies.ron`
+#[derive(Deserialize, Serialize, Debug, Hash, PartialEq, Eq, Clone)]
+#[serde(transparent)]
+pub struct EnemyKind(pub String);
+
+/// How an enemy kind moves and attacks
+#[derive(Deserialize, Debug, Clone)]
+pub enum EnemyBehavior {
+    /// Hops in place on a timer
+    Hopper {
+        // in seconds
+        jump_interval: f32,
+        jump_impulse: f32,
+        jump_torque_impulse: f32,
+    },
+    /// Walks back and forth around its position
+    Patrol {
+        // in pixels per second
+        speed: f32,
+        // in pixels, on each side
+        distance: f32,
+    },
+    /// Stays in place and fires at the player in attack range
+    Turret {
+        // in seconds
+        fire_interval: f32,
+        // in pixels per second
+        projectile_speed: f32,
+    },
+    /// Flies toward the player once alerted
+    Chaser {
+        // in pixels per second
+        speed: f32,
+    },
+}
+
+#[derive(Deserialize, Debug)]
+pub struct EnemyKindData {
+    pub sprite: String,
+    #[serde(default = "default_tint")]
+    pub tint: (f32, f32, f32),
+    // radius of the collider, in pixels
+    pub radius: f32,
+    pub health: u32,
+    // added to the score of the run when killed
+    pub score: usize,
+    pub behavior: EnemyBehavior,
+    pub ai: EnemyAiData,
+    // kinds without a drop table never drop letters
+    #[serde(default)]
+    pub drops: DropTable,
+}
+
+fn default_tint() -> (f32, f32, f32) {
+    (1.0, 1.0, 1.0)
+}
+
+#[derive(Deserialize)]
+pub struct EnemyKindsResource {
+    pub kinds: HashMap<EnemyKind, EnemyKindData>,
+}
+
+impl DataResource for EnemyKindsResource {
+    const PATH: &'static str = "data/enemies.ron";
 }
 
 #[derive(Debug, Component)]
-pub struct Enemy;
+pub struct Enemy {
+    pub kind: EnemyKind,
+    pub health: u32,
+    pub score: usize,
+}
 
 #[derive(Debug, Component)]
-pub struct SlimeEnemy {
+pub struct HopperEnemy {
     pub jump_timer: Timer,
     pub jump_impulse: f32,
     pub jump_torque_impulse: f32,
 }
 
+#[derive(Debug, Component)]
+pub struct PatrolEnemy {
+    // in pixels
+    origin_x: f32,
+    distance: f32,
+    speed: f32,
+    // 1.0 to the right, -1.0 to the left
+    direction: f32,
+}
+
+#[derive(Debug, Component)]
+pub struct TurretEnemy {
+    fire_timer: Timer,
+    projectile_speed: f32,
+}
+
+#[derive(Debug, Component)]
+pub struct ChaserEnemy {
+    speed: f32,
+}
+
 pub fn spawn_enemy(
     enemy_kind: &EnemyKind,
     position: Vec2,
     commands: &mut Commands,
     rapier_config: &RapierConfiguration,
     asset_server: &AssetServer,
-) {
-    match enemy_kind {
-        EnemyKind::Slime => {
-            spawn_slime(position, commands, rapier_config, asset_server);
+    enemy_kinds: &EnemyKindsResource,
+) -> Option<Entity> {
+    let enemy_data = match enemy_kinds.kinds.get(enemy_kind) {
+        Some(enemy_data) => enemy_data,
+        None => {
+            error!("Unknown enemy kind: {:?}", enemy_kind);
+            return None;
         }
-    }
-}
+    };
 
-fn spawn_slime(
-    position: Vec2,
-    commands: &mut Commands,
-    rapier_config: &RapierConfiguration,
-    asset_server: &AssetServer,
-) {
-    let collider_radius = 26.0 / rapier_config.scale / 2.0;
+    // turrets are bolted in place and chasers fly
+    let (body_type, mass_flags, gravity_scale) = match enemy_data.behavior {
+        EnemyBehavior::Hopper { .. } => (
+            RigidBodyType::Dynamic,
+            RigidBodyMassPropsFlags::TRANSLATION_LOCKED_X,
+            1.0,
+        ),
+        EnemyBehavior::Patrol { .. } => (
+            RigidBodyType::Dynamic,
+            RigidBodyMassPropsFlags::ROTATION_LOCKED,
+            1.0,
+        ),
+        EnemyBehavior::Turret { .. } => {
+            (RigidBodyType::Static, RigidBodyMassPropsFlags::empty(), 1.0)
+        }
+        EnemyBehavior::Chaser { .. } => (
+            RigidBodyType::Dynamic,
+            RigidBodyMassPropsFlags::ROTATION_LOCKED,
+            0.0,
+        ),
+    };
 
-    commands
-        .spawn_bundle(SpriteBundle {
-            texture: asset_server.load("drone.png"),
-            transform: Transform {
-                translation: Vec3::new(0.0, 0.0, 51.0),
-                ..Default::default()
-            },
+    let mut enemy = commands.spawn_bundle(SpriteBundle {
+        sprite: Sprite {
+            color: Color::rgb(enemy_data.tint.0, enemy_data.tint.1, enemy_data.tint.2),
             ..Default::default()
-        })
+        },
+        texture: asset_server.load(enemy_data.sprite.as_str()),
+        transform: Transform {
+            translation: Vec3::new(0.0, 0.0, 51.0),
+            ..Default::default()
+        },
+        ..Default::default()
+    });
+
+    enemy
         .insert_bundle(RigidBodyBundle {
-            body_type: RigidBodyType::Dynamic.into(),
-            position: Vec2::new(
-                position.x / rapier_config.scale,
-                position.y / rapier_config.scale,
-            )
+            body_type: body_type.into(),
+            position: (position / rapier_config.scale).into(),
+            mass_properties: mass_flags.into(),
+            forces: RigidBodyForces {
+                gravity_scale,
+                ..Default::default()
+            }
             .into(),
-            mass_properties: RigidBodyMassPropsFlags::TRANSLATION_LOCKED_X.into(),
             ..Default::default()
         })
         .insert_bundle(ColliderBundle {
-            shape: ColliderShape::ball(collider_radius).into(),
+            shape: ColliderShape::ball(enemy_data.radius / rapier_config.scale).into(),
             material: ColliderMaterial {
                 friction: 0.5,
                 restitution: 0.2,
@@ -80,13 +232,54 @@ fn spawn_slime(
             ..Default::default()
         })
         .insert(ColliderPositionSync::Discrete)
-        .insert(Enemy)
-        .insert(SlimeEnemy {
-            jump_timer: Timer::from_seconds(3.0, true),
-            jump_impulse: 550.0,
-            jump_torque_impulse: 30.0,
+        .insert(Enemy {
+            kind: enemy_kind.clone(),
+            health: enemy_data.health,
+            score: enemy_data.score,
         })
-        .insert(Name::new("Enemy-Slime"));
+        .insert(Name::new(format!("Enemy-{}", enemy_kind.0)));
+
+    let resting_state = match enemy_data.behavior {
+        EnemyBehavior::Patrol { .. } => EnemyState::Patrol,
+        _ => EnemyState::Idle,
+    };
+    enemy.insert(EnemyAi::new(enemy_data.ai.clone(), resting_state));
+
+    match enemy_data.behavior {
+        EnemyBehavior::Hopper {
+            jump_interval,
+            jump_impulse,
+            jump_torque_impulse,
+        } => {
+            enemy.insert(HopperEnemy {
+                jump_timer: Timer::from_seconds(jump_interval, true),
+                jump_impulse,
+                jump_torque_impulse,
+            });
+        }
+        EnemyBehavior::Patrol { speed, distance } => {
+            enemy.insert(PatrolEnemy {
+                origin_x: position.x,
+                distance,
+                speed,
+                direction: -1.0,
+            });
+        }
+        EnemyBehavior::Turret {
+            fire_interval,
+            projectile_speed,
+        } => {
+            enemy.insert(TurretEnemy {
+                fire_timer: Timer::from_seconds(fire_interval, true),
+                projectile_speed,
+            });
+        }
+        EnemyBehavior::Chaser { speed } => {
+            enemy.insert(ChaserEnemy { speed });
+        }
+    }
+
+    Some(enemy.id())
 }
 
 /// Test spawn platform
@@ -95,30 +288,103 @@ fn spawn_test_enemy(
     mut commands: Commands,
     rapier_config: Res<RapierConfiguration>,
     asset_server: Res<AssetServer>,
+    enemy_kinds: Res<EnemyKindsResource>,
 ) {
     spawn_enemy(
-        &EnemyKind::Slime,
+        &EnemyKind("Slime".to_string()),
         [75.0, -150.0].into(),
         &mut commands,
         &rapier_config,
         &asset_server,
+        &enemy_kinds,
     );
 }
 
-fn slime_enemy_behavior(
+fn hopper_enemy_behavior(
     time: Res<Time>,
-    mut slime_query: Query<(
-        &mut SlimeEnemy,
+    mut hopper_query: Query<(
+        &mut HopperEnemy,
+        &EnemyAi,
         &mut RigidBodyVelocityComponent,
         &RigidBodyMassPropsComponent,
     )>,
 ) {
-    for (mut slime_enemy, mut rb_vel, rb_mprops) in slime_query.iter_mut() {
-        slime_enemy.jump_timer.tick(time.delta());
-        if slime_enemy.jump_timer.just_finished() {
+    for (mut hopper_enemy, ai, mut rb_vel, rb_mprops) in hopper_query.iter_mut() {
+        if matches!(ai.state, EnemyState::Stunned | EnemyState::Dead) {
+            continue;
+        }
+
+        hopper_enemy.jump_timer.tick(time.delta());
+        if hopper_enemy.jump_timer.just_finished() {
             // Apply impulses.
-            jump(slime_enemy.jump_impulse, &mut rb_vel, rb_mprops);
-            rb_vel.apply_torque_impulse(rb_mprops, slime_enemy.jump_torque_impulse);
+            jump(hopper_enemy.jump_impulse, &mut rb_vel, rb_mprops);
+            rb_vel.apply_torque_impulse(rb_mprops, hopper_enemy.jump_torque_impulse);
         }
     }
 }
+
+fn patrol_enemy_behavior(
+    rapier_config: Res<RapierConfiguration>,
+    mut patrol_query: Query<(
+        &mut PatrolEnemy,
+        &EnemyAi,
+        &RigidBodyPositionComponent,
+        &mut RigidBodyVelocityComponent,
+    )>,
+) {
+    for (mut patrol_enemy, ai, rb_pos, mut rb_vel) in patrol_query.iter_mut() {
+        let speed = match ai.state {
+            EnemyState::Patrol => {
+                let offset =
+                    rb_pos.position.translation.x * rapier_config.scale - patrol_enemy.origin_x;
+
+                // turn around at the end of the route
+                if offset * patrol_enemy.direction >= patrol_enemy.distance {
+                    patrol_enemy.direction = -patrol_enemy.direction;
+                }
+                patrol_enemy.direction * patrol_enemy.speed
+            }
+            EnemyState::Attack => ai.to_player.x.signum() * patrol_enemy.speed * CHARGE_FACTOR,
+            // stands still, watching the player
+            _ => 0.0,
+        };
+
+        rb_vel.linvel.x = speed / rapier_config.scale;
+    }
+}
+
+fn turret_enemy_behavior(
+    mut commands: Commands,
+    time: Res<Time>,
+    rapier_config: Res<RapierConfiguration>,
+    mut turret_query: Query<(&mut TurretEnemy, &EnemyAi, &Transform)>,
+) {
+    for (mut turret_enemy, ai, transform) in turret_query.iter_mut() {
+        turret_enemy.fire_timer.tick(time.delta());
+
+        if turret_enemy.fire_timer.just_finished() && ai.state == EnemyState::Attack {
+            projectile::spawn_projectile(
+                transform.translation.truncate(),
+                ai.to_player.normalize_or_zero() * turret_enemy.projectile_speed,
+                &mut commands,
+                &rapier_config,
+            );
+        }
+    }
+}
+
+fn chaser_enemy_behavior(
+    rapier_config: Res<RapierConfiguration>,
+    mut chaser_query: Query<(&ChaserEnemy, &EnemyAi, &mut RigidBodyVelocityComponent)>,
+) {
+    for (chaser_enemy, ai, mut rb_vel) in chaser_query.iter_mut() {
+        // hovers until alerted
+        let velocity = match ai.state {
+            EnemyState::Alert | EnemyState::Attack => {
+                ai.to_player.normalize_or_zero() * chaser_enemy.speed / rapier_config.scale
+            }
+            _ => Vec2::ZERO,
+        };
+        rb_vel.linvel = velocity.into();
+    }
+}
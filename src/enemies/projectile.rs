@@ -0,0 +1,119 @@
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use crate::runner::{damage_player, GameOverEvent, Player};
+use crate::states::GameStates;
+
+// in seconds
+const PROJECTILE_LIFETIME: f32 = 4.0;
+// in pixels
+const PROJECTILE_RADIUS: f32 = 6.0;
+
+/// Shot fired by a turret, hurts the player on contact
+#[derive(Debug, Component)]
+pub struct Projectile {
+    lifetime: Timer,
+}
+
+/// Spawn a projectile, `velocity` being in pixels per second
+pub fn spawn_projectile(
+    position: Vec2,
+    velocity: Vec2,
+    commands: &mut Commands,
+    rapier_config: &RapierConfiguration,
+) -> Entity {
+    commands
+        .spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: Color::rgb(1.0, 0.4, 0.1),
+                custom_size: Some(Vec2::splat(PROJECTILE_RADIUS * 2.0)),
+                ..Default::default()
+            },
+            transform: Transform::from_translation(position.extend(53.0)),
+            ..Default::default()
+        })
+        .insert_bundle(RigidBodyBundle {
+            body_type: RigidBodyType::KinematicVelocityBased.into(),
+            position: (position / rapier_config.scale).into(),
+            velocity: RigidBodyVelocity {
+                linvel: (velocity / rapier_config.scale).into(),
+                ..Default::default()
+            }
+            .into(),
+            ..Default::default()
+        })
+        .insert_bundle(ColliderBundle {
+            shape: ColliderShape::ball(PROJECTILE_RADIUS / rapier_config.scale).into(),
+            collider_type: ColliderType::Sensor.into(),
+            flags: ColliderFlags {
+                active_events: ActiveEvents::INTERSECTION_EVENTS,
+                ..Default::default()
+            }
+            .into(),
+            ..Default::default()
+        })
+        .insert(ColliderPositionSync::Discrete)
+        .insert(Projectile {
+            lifetime: Timer::from_seconds(PROJECTILE_LIFETIME, false),
+        })
+        .insert(Name::new("Projectile"))
+        .id()
+}
+
+pub fn projectile_hit(
+    mut commands: Commands,
+    mut intersection_events: EventReader<IntersectionEvent>,
+    mut player_query: Query<(Entity, &mut Player)>,
+    projectile_query: Query<Entity, With<Projectile>>,
+    mut game_over_event: EventWriter<GameOverEvent>,
+    mut game_state: ResMut<State<GameStates>>,
+) {
+    let (player_entity, mut player) = match player_query.iter_mut().next() {
+        Some(player) => player,
+        None => return,
+    };
+
+    for event in intersection_events.iter() {
+        if !event.intersecting {
+            continue;
+        }
+
+        let collider1_entity = event.collider1.entity();
+        let collider2_entity = event.collider2.entity();
+
+        let projectile_entity = if collider1_entity == player_entity {
+            collider2_entity
+        } else if collider2_entity == player_entity {
+            collider1_entity
+        } else {
+            continue;
+        };
+
+        if projectile_query.get(projectile_entity).is_ok() {
+            damage_player(&mut player, &mut game_over_event, &mut game_state);
+            commands.entity(projectile_entity).despawn_recursive();
+        }
+    }
+}
+
+pub fn expire_projectiles(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut projectile_query: Query<(Entity, &mut Projectile)>,
+) {
+    for (entity, mut projectile) in projectile_query.iter_mut() {
+        projectile.lifetime.tick(time.delta());
+        if projectile.lifetime.finished() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+pub fn despawn_projectiles(
+    mut commands: Commands,
+    projectile_query: Query<Entity, With<Projectile>>,
+) {
+    for entity in projectile_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -1,6 +1,7 @@
 use bevy::{app::AppExit, prelude::*};
 
 use crate::{
+    controls::InputAction,
     pause_menu::button::{UIButton, HOVERED_BUTTON, NORMAL_BUTTON, PRESSED_BUTTON},
     states::GameStates,
 };
@@ -24,12 +25,12 @@ impl Plugin for GameOverPlugin {
 }
 
 fn open_gameover_screen(
-    mut keyboard: ResMut<Input<KeyCode>>,
+    mut actions: ResMut<Input<InputAction>>,
     mut game_state: ResMut<State<GameStates>>,
 ) {
-    if keyboard.just_pressed(KeyCode::G) {
+    if actions.just_pressed(InputAction::GiveUp) {
         game_state.push(GameStates::GameOver).unwrap();
-        keyboard.reset(KeyCode::G);
+        actions.reset(InputAction::GiveUp);
     }
 }
 
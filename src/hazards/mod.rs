@@ -0,0 +1,190 @@
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::runner::{damage_player, GameOverEvent, Player, PlayerState};
+use crate::states::GameStates;
+
+// opacity of the sprite of a hazard turned off
+const INACTIVE_ALPHA: f32 = 0.15;
+
+pub struct HazardsPlugin;
+
+impl Plugin for HazardsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::on_update(GameStates::Main)
+                .with_system(toggle_hazards)
+                .with_system(detect_hazard_contact.label("hazard_contact"))
+                .with_system(hazard_damage.after("hazard_contact").after("player_state")),
+        );
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq, Clone)]
+pub enum HazardKind {
+    // always on, only a dash gets through them unharmed
+    Spikes,
+    // beam at head height, crouch under it or wait for it to turn off
+    Laser { on_time: f32, off_time: f32 },
+    // pulsing floor tiles, jump over them or dash across
+    ElectrifiedFloor { on_time: f32, off_time: f32 },
+}
+
+impl HazardKind {
+    fn color(&self) -> Color {
+        match self {
+            HazardKind::Spikes => Color::rgb(0.6, 0.6, 0.65),
+            HazardKind::Laser { .. } => Color::rgb(1.0, 0.1, 0.2),
+            HazardKind::ElectrifiedFloor { .. } => Color::rgb(0.3, 0.8, 1.0),
+        }
+    }
+
+    // on and off durations of the hazards turning on and off
+    fn cycle(&self) -> Option<(f32, f32)> {
+        match self {
+            HazardKind::Spikes => None,
+            HazardKind::Laser { on_time, off_time }
+            | HazardKind::ElectrifiedFloor { on_time, off_time } => Some((*on_time, *off_time)),
+        }
+    }
+
+    /// Whether the player is out of reach of the hazard
+    fn is_avoided_by(&self, player: &Player) -> bool {
+        match self {
+            HazardKind::Laser { .. } => player.state == PlayerState::Crouch,
+            _ => false,
+        }
+    }
+}
+
+#[derive(Debug, Component)]
+pub struct Hazard {
+    pub kind: HazardKind,
+    pub is_active: bool,
+    pub touching_player: bool,
+}
+
+/// Turns a hazard on and off
+#[derive(Debug, Component)]
+pub struct HazardTimer(Timer);
+
+/// Spawn a hazard, `size` being its full size in pixels
+pub fn spawn_hazard(
+    hazard_kind: &HazardKind,
+    position: Vec2,
+    size: Vec2,
+    commands: &mut Commands,
+    rapier_config: &RapierConfiguration,
+) -> Entity {
+    let mut hazard = commands.spawn_bundle(SpriteBundle {
+        sprite: Sprite {
+            color: hazard_kind.color(),
+            custom_size: Some(size),
+            ..Default::default()
+        },
+        transform: Transform::from_translation(position.extend(52.0)),
+        ..Default::default()
+    });
+
+    hazard
+        .insert_bundle(ColliderBundle {
+            shape: ColliderShape::cuboid(
+                size.x / rapier_config.scale / 2.0,
+                size.y / rapier_config.scale / 2.0,
+            )
+            .into(),
+            position: [
+                position.x / rapier_config.scale,
+                position.y / rapier_config.scale,
+            ]
+            .into(),
+            collider_type: ColliderType::Sensor.into(),
+            flags: ColliderFlags {
+                active_events: ActiveEvents::INTERSECTION_EVENTS,
+                ..Default::default()
+            }
+            .into(),
+            ..Default::default()
+        })
+        .insert(Hazard {
+            kind: hazard_kind.clone(),
+            is_active: true,
+            touching_player: false,
+        })
+        .insert(Name::new(format!("Hazard-{:?}", hazard_kind)));
+
+    if let Some((on_time, _)) = hazard_kind.cycle() {
+        hazard.insert(HazardTimer(Timer::from_seconds(on_time, false)));
+    }
+
+    hazard.id()
+}
+
+fn toggle_hazards(
+    time: Res<Time>,
+    mut hazard_query: Query<(&mut Hazard, &mut HazardTimer, &mut Sprite)>,
+) {
+    for (mut hazard, mut timer, mut sprite) in hazard_query.iter_mut() {
+        timer.0.tick(time.delta());
+        if !timer.0.finished() {
+            continue;
+        }
+
+        if let Some((on_time, off_time)) = hazard.kind.cycle() {
+            hazard.is_active = !hazard.is_active;
+            let duration = if hazard.is_active { on_time } else { off_time };
+            timer.0 = Timer::from_seconds(duration, false);
+            sprite.color.set_a(if hazard.is_active {
+                1.0
+            } else {
+                INACTIVE_ALPHA
+            });
+        }
+    }
+}
+
+fn detect_hazard_contact(
+    mut intersection_events: EventReader<IntersectionEvent>,
+    player_query: Query<Entity, With<Player>>,
+    mut hazard_query: Query<&mut Hazard>,
+) {
+    let player_entity = match player_query.iter().next() {
+        Some(entity) => entity,
+        None => return,
+    };
+
+    for event in intersection_events.iter() {
+        let collider1_entity = event.collider1.entity();
+        let collider2_entity = event.collider2.entity();
+
+        let hazard_entity = if collider1_entity == player_entity {
+            collider2_entity
+        } else if collider2_entity == player_entity {
+            collider1_entity
+        } else {
+            continue;
+        };
+
+        if let Ok(mut hazard) = hazard_query.get_mut(hazard_entity) {
+            hazard.touching_player = event.intersecting;
+        }
+    }
+}
+
+fn hazard_damage(
+    mut player_query: Query<&mut Player>,
+    hazard_query: Query<&Hazard>,
+    mut game_over_event: EventWriter<GameOverEvent>,
+    mut game_state: ResMut<State<GameStates>>,
+) {
+    for mut player in player_query.iter_mut() {
+        let is_hurt = hazard_query.iter().any(|hazard| {
+            hazard.touching_player && hazard.is_active && !hazard.kind.is_avoided_by(&player)
+        });
+
+        if is_hurt {
+            damage_player(&mut player, &mut game_over_event, &mut game_state);
+        }
+    }
+}
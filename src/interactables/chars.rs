@@ -13,7 +13,7 @@ pub fn spawn_char(
     texture_atlases: &mut Assets<TextureAtlas>,
     value: char,
     position: &Vec2,
-) {
+) -> Entity {
     let interactable_type = InteractableType::CharText;
     let path = format!("chars/{}_key.png", value);
 
@@ -35,5 +35,6 @@ pub fn spawn_char(
             interactable_type,
             range: 25.0,
         })
-        .insert(CharTextComponent { value });
+        .insert(CharTextComponent { value })
+        .id()
 }
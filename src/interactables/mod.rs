@@ -22,4 +22,6 @@ impl Plugin for InteractablesPlugin {
 }
 
 pub use chars::{spawn_char, CharTextComponent};
-pub use terminal::spawn_terminal;
+pub use terminal::{
+    spawn_terminal, TerminalComponent, TerminalLockout, TERMINAL_MAX_FAILED_ATTEMPTS,
+};
@@ -6,11 +6,53 @@ use super::{InteractableComponent, InteractableType};
 #[derive(Component)]
 pub struct TerminalAnimationTimer(Timer);
 
+/// Terminal refusing connections after a failed hack
+#[derive(Component)]
+pub struct TerminalLockout(pub Timer);
+
+// number of cheat codes that can be activated on a single terminal
+pub const TERMINAL_ACTIVATIONS: u8 = 2;
+// failed activations before the terminal locks itself
+pub const TERMINAL_MAX_FAILED_ATTEMPTS: u8 = 3;
+
+#[derive(Component)]
+pub struct TerminalComponent {
+    pub activations_left: u8,
+    pub failed_attempts: u8,
+    // counted from the start of the run, the entity ids get recycled
+    pub number: u32,
+}
+
+impl TerminalComponent {
+    pub fn is_depleted(&self) -> bool {
+        self.activations_left == 0
+    }
+
+    /// Short identifier displayed when connected to the terminal
+    pub fn display_id(&self) -> String {
+        format!("T-{:04}", self.number)
+    }
+}
+
+/// Terminals spawned since the start of the run
+#[derive(Default)]
+pub struct TerminalCounter(u32);
+
 pub struct InteractableTerminalPlugin;
 
 impl Plugin for InteractableTerminalPlugin {
     fn build(&self, app: &mut App) {
-        app.add_system_set(SystemSet::on_update(GameStates::Main).with_system(animate_sprite));
+        app.init_resource::<TerminalCounter>()
+            .add_system_set(
+                SystemSet::on_enter(GameStates::Main).with_system(reset_terminal_counter),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameStates::Main)
+                    .with_system(number_terminals)
+                    .with_system(animate_sprite)
+                    .with_system(update_lockouts)
+                    .with_system(update_appearance),
+            );
     }
 }
 
@@ -19,7 +61,7 @@ pub fn spawn_terminal(
     asset_server: &AssetServer,
     texture_atlases: &mut Assets<TextureAtlas>,
     position: &Vec2,
-) {
+) -> Entity {
     let interactable_type = InteractableType::Terminal;
 
     let texture_handle = asset_server.load("terminal.png");
@@ -41,7 +83,27 @@ pub fn spawn_terminal(
             interactable_type,
             range: 65.0,
         })
-        .insert(Name::new("Terminal"));
+        .insert(TerminalComponent {
+            activations_left: TERMINAL_ACTIVATIONS,
+            failed_attempts: 0,
+            number: 0,
+        })
+        .insert(Name::new("Terminal"))
+        .id()
+}
+
+fn reset_terminal_counter(mut counter: ResMut<TerminalCounter>) {
+    counter.0 = 0;
+}
+
+fn number_terminals(
+    mut counter: ResMut<TerminalCounter>,
+    mut terminal_query: Query<&mut TerminalComponent, Added<TerminalComponent>>,
+) {
+    for mut terminal in terminal_query.iter_mut() {
+        counter.0 += 1;
+        terminal.number = counter.0;
+    }
 }
 
 pub fn animate_sprite(
@@ -51,9 +113,15 @@ pub fn animate_sprite(
         &mut TerminalAnimationTimer,
         &mut TextureAtlasSprite,
         &Handle<TextureAtlas>,
+        &TerminalComponent,
     )>,
 ) {
-    for (mut timer, mut sprite, texture_atlas_handle) in query.iter_mut() {
+    for (mut timer, mut sprite, texture_atlas_handle, terminal) in query.iter_mut() {
+        // used up terminals are turned off
+        if terminal.is_depleted() {
+            continue;
+        }
+
         timer.0.tick(time.delta());
         if timer.0.just_finished() {
             let texture_atlas = texture_atlases.get(texture_atlas_handle).unwrap();
@@ -61,3 +129,34 @@ pub fn animate_sprite(
         }
     }
 }
+
+pub fn update_lockouts(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut TerminalLockout)>,
+) {
+    for (entity, mut lockout) in query.iter_mut() {
+        lockout.0.tick(time.delta());
+        if lockout.0.finished() {
+            commands.entity(entity).remove::<TerminalLockout>();
+        }
+    }
+}
+
+pub fn update_appearance(
+    mut query: Query<(
+        &TerminalComponent,
+        Option<&TerminalLockout>,
+        &mut TextureAtlasSprite,
+    )>,
+) {
+    for (terminal, lockout, mut sprite) in query.iter_mut() {
+        sprite.color = if terminal.is_depleted() {
+            Color::rgb(0.3, 0.3, 0.3)
+        } else if lockout.is_some() {
+            Color::rgb(1.0, 0.4, 0.4)
+        } else {
+            Color::WHITE
+        };
+    }
+}
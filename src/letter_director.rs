@@ -0,0 +1,153 @@
+use bevy::prelude::*;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use ron::de::from_bytes;
+use serde::Deserialize;
+
+use crate::cheat_codes::{CheatCodeKind, CheatCodeResource};
+use crate::platforms::data::{self, DataResource};
+use crate::runner::{CollectedChars, LETTERS};
+use crate::states::GameStates;
+
+pub struct LetterDirectorPlugin;
+
+impl Plugin for LetterDirectorPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(
+            from_bytes::<LetterDirector>(include_bytes!("../assets/data/letters.ron")).unwrap(),
+        )
+        .add_startup_system(data::load_data::<LetterDirector>)
+        .add_system(data::reload_data::<LetterDirector>)
+        .add_system_set(
+            SystemSet::on_enter(GameStates::Main)
+                .with_system(reset_director.before("generate_prelude_chunk")),
+        )
+        .add_system_set(
+            SystemSet::on_update(GameStates::Main)
+                .with_system(update_director)
+                .with_system(log_economy),
+        );
+    }
+}
+
+/// Steers the random letters toward the code the player should go for next,
+/// tuned in `assets/data/letters.ron`
+#[derive(Deserialize)]
+pub struct LetterDirector {
+    // chance for a random letter to be one the player is missing
+    pub generosity: f32,
+    // in seconds
+    pub log_interval: f32,
+    #[serde(skip)]
+    pub target: Option<CheatCodeKind>,
+    // letters of the target code the player doesn't have, duplicates included
+    #[serde(skip)]
+    pub missing: Vec<char>,
+    #[serde(skip)]
+    steered_letters: u32,
+    #[serde(skip)]
+    random_letters: u32,
+    #[serde(skip)]
+    log_timer: Timer,
+}
+
+impl DataResource for LetterDirector {
+    const PATH: &'static str = "data/letters.ron";
+
+    // the state of the run is kept
+    fn reload(&mut self, new: Self) {
+        self.generosity = new.generosity;
+        self.log_interval = new.log_interval;
+    }
+}
+
+impl LetterDirector {
+    /// Letter of a random slot
+    pub fn pick_letter(&mut self) -> char {
+        let mut rng = rand::thread_rng();
+
+        if !self.missing.is_empty() && rng.gen_bool(self.generosity.clamp(0.0, 1.0) as f64) {
+            self.steered_letters += 1;
+            *self.missing.choose(&mut rng).unwrap()
+        } else {
+            self.random_letters += 1;
+            *LETTERS.choose(&mut rng).unwrap()
+        }
+    }
+
+    /// Letter dropped by a killed enemy, always a missing one when `favor_target` is set
+    pub fn pick_drop(&mut self, favor_target: bool) -> char {
+        if favor_target && !self.missing.is_empty() {
+            self.steered_letters += 1;
+            *self.missing.choose(&mut rand::thread_rng()).unwrap()
+        } else {
+            self.pick_letter()
+        }
+    }
+
+    fn refresh(&mut self, cheat_codes: &CheatCodeResource, collected_chars: &CollectedChars) {
+        // the target is the code rolled by the cheat codes, so the terminals reward the same one
+        let next_code = cheat_codes.get_next_code();
+        if next_code != self.target {
+            if let Some(kind) = next_code {
+                info!("[LetterDirector] New target code: {:?}", kind);
+            }
+            self.target = next_code;
+        }
+
+        let code = match self.target.and_then(|kind| cheat_codes.codes.get(&kind)) {
+            Some(code) => code,
+            None => {
+                // every code is activated, nothing is left to steer toward
+                self.missing.clear();
+                return;
+            }
+        };
+
+        let mut available = collected_chars.values.clone();
+        self.missing.clear();
+        for ch in code.text.chars() {
+            match available.iter().position(|collected| *collected == ch) {
+                Some(index) => {
+                    available.swap_remove(index);
+                }
+                None => self.missing.push(ch),
+            }
+        }
+    }
+}
+
+fn reset_director(
+    mut director: ResMut<LetterDirector>,
+    cheat_codes: Res<CheatCodeResource>,
+    collected_chars: Res<CollectedChars>,
+) {
+    director.target = None;
+    director.steered_letters = 0;
+    director.random_letters = 0;
+    director.log_timer = Timer::from_seconds(director.log_interval, true);
+    director.refresh(&cheat_codes, &collected_chars);
+}
+
+fn update_director(
+    mut director: ResMut<LetterDirector>,
+    cheat_codes: Res<CheatCodeResource>,
+    collected_chars: Res<CollectedChars>,
+) {
+    if cheat_codes.is_changed() || collected_chars.is_changed() {
+        director.refresh(&cheat_codes, &collected_chars);
+    }
+}
+
+fn log_economy(time: Res<Time>, mut director: ResMut<LetterDirector>) {
+    director.log_timer.tick(time.delta());
+    if director.log_timer.just_finished() {
+        info!(
+            "[LetterDirector] target: {:?}, missing: {:?}, steered letters: {}/{}",
+            director.target,
+            director.missing,
+            director.steered_letters,
+            director.steered_letters + director.random_letters
+        );
+    }
+}
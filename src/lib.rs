@@ -0,0 +1,23 @@
+pub mod biomes;
+pub mod bosses;
+pub mod camera;
+pub mod cheat_codes;
+pub mod chunk_editor;
+pub mod console;
+pub mod controls;
+pub mod effects;
+pub mod enemies;
+pub mod game_over;
+pub mod hazards;
+pub mod interactables;
+pub mod letter_director;
+pub mod letter_gutter;
+pub mod main_menu;
+pub mod pause_menu;
+pub mod physics;
+pub mod platforms;
+pub mod runner;
+pub mod states;
+pub mod stats;
+pub mod tab_menu;
+pub mod toast;
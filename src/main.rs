@@ -6,32 +6,29 @@ use bevy_inspector_egui::WorldInspectorPlugin;
 use bevy_kira_audio::AudioPlugin;
 use bevy_rapier2d::prelude::*;
 
-use cheat_codes::CheatCodeResource;
-use toast::ShowToast;
-
-mod camera;
-mod cheat_codes;
-mod console;
-mod effects;
-mod enemies;
-mod game_over;
-mod interactables;
-mod letter_gutter;
-mod main_menu;
-mod pause_menu;
-mod physics;
-mod platforms;
-mod runner;
-mod states;
-mod stats;
-mod tab_menu;
-mod toast;
+use bevy_jam_1::cheat_codes::{self, CheatCodeResource};
+#[cfg(debug_assertions)]
+use bevy_jam_1::chunk_editor;
+use bevy_jam_1::toast::{self, ShowToast};
+use bevy_jam_1::{
+    biomes, bosses, camera, console, controls, effects, enemies, game_over, hazards, interactables,
+    letter_director, letter_gutter, main_menu, pause_menu, physics, platforms, runner, states,
+    stats, tab_menu,
+};
 
 fn main() {
     let mut app = App::new();
 
     #[cfg(debug_assertions)]
-    app.add_plugin(WorldInspectorPlugin::new());
+    app.add_plugin(WorldInspectorPlugin::new())
+        .add_plugin(chunk_editor::ChunkEditorPlugin);
+
+    // reload the files of assets/data when they change
+    #[cfg(debug_assertions)]
+    app.insert_resource(bevy::asset::AssetServerSettings {
+        watch_for_changes: true,
+        ..Default::default()
+    });
 
     app.insert_resource(WindowDescriptor {
         resizable: false,
@@ -43,6 +40,7 @@ fn main() {
     .insert_resource(cheat_codes::CheatCodeResource::new())
     .add_plugin(main_menu::MainMenuPlugin)
     .add_plugins(DefaultPlugins)
+    .add_plugin(controls::ControlsPlugin)
     .add_plugin(tab_menu::TabMenuPlugin)
     .add_plugin(console::ConsolePlugin)
     .add_plugin(runner::RunnerPlugin)
@@ -50,15 +48,20 @@ fn main() {
     .add_plugin(RapierPhysicsPlugin::<NoUserData>::default())
     .add_plugin(physics::PhysicsPlugin)
     .add_plugin(platforms::PlatformsPlugin)
+    .add_plugin(biomes::BiomesPlugin)
     .add_plugin(enemies::EnemiesPlugin)
+    .add_plugin(hazards::HazardsPlugin)
+    .add_plugin(bosses::BossesPlugin)
     .add_plugin(toast::ToastPlugin)
     .add_plugin(game_over::GameOverPlugin)
     .add_plugin(interactables::InteractablesPlugin)
     .add_plugin(letter_gutter::LetterGutterPlugin)
+    .add_plugin(letter_director::LetterDirectorPlugin)
     .add_plugin(AudioPlugin)
     .add_state(states::GameStates::MainMenu)
     .add_plugin(stats::GameStatsPlugin)
     .add_plugin(effects::EffectsPlugin)
+    .init_resource::<camera::CameraLock>()
     .add_startup_system(camera::add_camera)
     // TODO: remove
     .add_startup_system(test_codes)
@@ -72,7 +75,7 @@ fn test_codes(mut cheat_codes_res: ResMut<CheatCodeResource>) {
         cheat_codes::generate_random_code(cheat_codes::CheatCodeRarity::Legendary)
     );
 
-    let next_code = cheat_codes_res.get_next_code();
+    let next_code = cheat_codes_res.get_next_code().unwrap();
     let next_code_code = cheat_codes_res.codes.get(&next_code).unwrap();
     println!(
         "Get next cheat code: {:?} with code: {}",
@@ -92,14 +95,14 @@ fn test_codes(mut cheat_codes_res: ResMut<CheatCodeResource>) {
     }
 }
 
-fn prelude_text(mut toasts: EventWriter<ShowToast>) {
+fn prelude_text(mut toasts: EventWriter<ShowToast>, bindings: Res<controls::InputBindings>) {
     // empty to avoid issues
     toasts.send(ShowToast {
-        value: "Press 'D' to move forward".to_string(),
+        value: bindings.fill("Press {MoveRight} to move forward"),
         duration: Duration::from_secs(3),
     });
     toasts.send(ShowToast {
-        value: "Press TAB to open journal".to_string(),
+        value: bindings.fill("Press {Journal} to open journal"),
         duration: Duration::from_secs(3),
     });
     toasts.send(ShowToast {
@@ -107,7 +110,7 @@ fn prelude_text(mut toasts: EventWriter<ShowToast>) {
         duration: Duration::from_secs(3),
     });
     toasts.send(ShowToast {
-        value: "Press `E` to interact with terminal".to_string(),
+        value: bindings.fill("Press {Interact} to interact with terminal"),
         duration: Duration::from_secs(3),
     });
     toasts.send(ShowToast {
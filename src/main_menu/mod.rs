@@ -29,7 +29,6 @@ impl Plugin for MainMenuPlugin {
         app.add_system_set(
             SystemSet::on_exit(GameStates::MainMenu)
                 .with_system(destroy_menu)
-                .with_system(start_gameplay_audio)
                 .with_system(despawn_main_menu_background)
         );
     }
@@ -61,6 +60,9 @@ pub fn button_handler(
                     "play" => {
                         game_state.set(GameStates::Main).unwrap();
                     }
+                    "editor" => {
+                        game_state.set(GameStates::ChunkEditor).unwrap();
+                    }
                     "quit" => {
                         exit.send(AppExit);
                     }
@@ -82,11 +84,6 @@ fn start_automation_audio(asset_server: Res<AssetServer>, audio: Res<Audio>) {
     audio.play_looped(asset_server.load("automation.ogg"));
 }
 
-fn start_gameplay_audio(asset_server: Res<AssetServer>, audio: Res<Audio>) {
-    audio.stop();
-    audio.play_looped(asset_server.load("cyberpunk_moonlight_sonata.ogg"));
-}
-
 fn spawn_main_menu_background(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
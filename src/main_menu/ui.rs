@@ -96,6 +96,12 @@ pub fn build_ui(
     //     font_handle.clone(),
     //     "options".to_string(),
     // );
+    #[cfg(debug_assertions)]
+    let editor_btn = UIButton::new(
+        "Chunk editor".to_string(),
+        font_handle.clone(),
+        "editor".to_string(),
+    );
     let quit_btn = UIButton::new("Quit".to_string(), font_handle.clone(), "quit".to_string());
 
     // ---------- UI TREE CONSTRUCTION ----------//
@@ -109,6 +115,8 @@ pub fn build_ui(
                 play_btn.spawn(parent);
                 // TODO: Add options back
                 // options_btn.spawn(parent);
+                #[cfg(debug_assertions)]
+                editor_btn.spawn(parent);
                 quit_btn.spawn(parent);
             });
         })
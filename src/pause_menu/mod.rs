@@ -2,6 +2,7 @@ use bevy::{app::AppExit, prelude::*};
 use bevy_kira_audio::{Audio, AudioChannel};
 use bevy_ninepatch::NinePatchPlugin;
 
+use crate::controls::InputAction;
 use crate::states::GameStates;
 
 use self::button::{UIButton, HOVERED_BUTTON, NORMAL_BUTTON, PRESSED_BUTTON};
@@ -40,22 +41,22 @@ fn destroy_menu(mut commands: Commands, query: Query<Entity, With<PauseMenuEntit
 }
 
 fn open_pause_menu(
-    mut keyboard: ResMut<Input<KeyCode>>,
+    mut actions: ResMut<Input<InputAction>>,
     mut game_state: ResMut<State<GameStates>>,
 ) {
-    if keyboard.just_pressed(KeyCode::Escape) {
+    if actions.just_pressed(InputAction::Pause) {
         game_state.push(GameStates::PauseMenu).unwrap();
-        keyboard.reset(KeyCode::Escape);
+        actions.reset(InputAction::Pause);
     }
 }
 
 fn close_pause_menu(
-    mut keyboard: ResMut<Input<KeyCode>>,
+    mut actions: ResMut<Input<InputAction>>,
     mut game_state: ResMut<State<GameStates>>,
 ) {
-    if keyboard.just_pressed(KeyCode::Escape) {
+    if actions.just_pressed(InputAction::Pause) {
         game_state.pop().unwrap();
-        keyboard.reset(KeyCode::Escape);
+        actions.reset(InputAction::Pause);
     }
 }
 
@@ -3,6 +3,12 @@ use bevy_rapier2d::prelude::*;
 
 use crate::states::GameStates;
 
+pub const GRAVITY: f32 = -140.0;
+// pixels per physics unit
+pub const PHYSICS_SCALE: f32 = 10.0;
+// solver group of the player body, see the one-way platforms
+pub const PLAYER_GROUP: u32 = 0b0001;
+
 pub struct PhysicsPlugin;
 
 impl Plugin for PhysicsPlugin {
@@ -14,8 +20,8 @@ impl Plugin for PhysicsPlugin {
 }
 
 fn setup_physics(mut rapier_config: ResMut<RapierConfiguration>) {
-    rapier_config.gravity = [0.0, -140.0].into();
-    rapier_config.scale = 10.0;
+    rapier_config.gravity = [0.0, GRAVITY].into();
+    rapier_config.scale = PHYSICS_SCALE;
 }
 
 pub fn jump(
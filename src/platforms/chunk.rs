@@ -1,27 +1,46 @@
 use bevy::prelude::*;
 use bevy_rapier2d::prelude::*;
 use rand::distributions::{Alphanumeric, DistString};
-use rand::{seq::SliceRandom, Rng};
-use serde::Deserialize;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
 
-use super::platform;
-use crate::cheat_codes::{randomize_text, CheatCodeKind, CheatCodeRarity, CheatCodeResource};
-use crate::interactables::{spawn_char, spawn_terminal, InteractableComponent};
-use crate::{enemies, runner};
+use super::data::DataResource;
+use super::difficulty::{DifficultyCurveResource, DifficultyPoint};
+use super::generator::{self, GeneratorRules};
+use super::{platform, reachability};
+use crate::biomes::{BiomeData, BiomesResource};
+use crate::camera::TwoDCameraComponent;
+use crate::cheat_codes::{CheatCodeKind, CheatCodeResource};
+use crate::interactables::{spawn_char, spawn_terminal};
+use crate::letter_director::LetterDirector;
+use crate::runner::{CodeFragment, RunSeed};
+use crate::stats::GameStatsResource;
+use crate::{enemies, hazards, runner};
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Serialize, Clone)]
 pub struct PlatformData {
     pub platform_kind: platform::PlatformKind,
     pub position: Vec2,
+    #[serde(default, skip_serializing_if = "platform::PlatformBehavior::is_static")]
+    pub behavior: platform::PlatformBehavior,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Serialize, Clone)]
 pub struct EnemyData {
     pub enemy_kind: enemies::EnemyKind,
     pub position: Vec2,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Serialize, Clone)]
+pub struct HazardData {
+    pub hazard_kind: hazards::HazardKind,
+    pub position: Vec2,
+    // in pixels
+    pub size: Vec2,
+}
+
+#[derive(Deserialize, Serialize, Clone)]
 pub struct CharData {
     pub cheat_kind: Option<CheatCodeKind>,
     pub positions: Vec<Vec2>,
@@ -31,21 +50,140 @@ pub struct CharData {
 #[derive(Deserialize)]
 pub struct ChunksResource {
     pub prelude_chunks: Vec<Chunk>,
-    pub basic_chunks: Vec<Chunk>,
-    pub jump_chunks: Vec<Chunk>,
-    // add chunk vec for each cheat
+    pub chunks: Vec<Chunk>,
     pub furthest_x: f32,
+    // last chunk spawned, the generated chunks have to be reachable from it
+    #[serde(skip)]
+    pub last_chunk: Option<Chunk>,
 }
 
-#[derive(Deserialize)]
+/// Parent of every entity spawned for a chunk
+#[derive(Debug, Component)]
+pub struct ChunkRoot {
+    // rightmost point of the chunk, in pixels
+    pub right_x: f32,
+}
+
+// how far behind the left edge of the screen a chunk is despawned, in pixels
+const DESPAWN_DISTANCE: f32 = 1000.0;
+
+#[derive(Deserialize, Serialize, Clone)]
 pub struct Chunk {
     pub platforms: Vec<PlatformData>,
     pub enemies: Vec<EnemyData>,
-    pub next_chunk_offset: f32,
-    pub chunk_offset: f32,
-    // ability dependency? optional?
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hazards: Vec<HazardData>,
     pub terminals: Vec<Vec2>,
     pub chars: Vec<CharData>,
+    // cheats needed to get past the chunk
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub required_cheats: Vec<CheatCodeKind>,
+    // cheats that are not needed but make the chunk more interesting
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rewarding_cheats: Vec<CheatCodeKind>,
+    // names of the biomes the chunk can appear in, any biome if empty
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub biomes: Vec<String>,
+    pub next_chunk_offset: f32,
+    pub chunk_offset: f32,
+    // from 1 (easy) to 3 (hard), see assets/data/difficulty.ron
+    #[serde(default = "default_difficulty")]
+    pub difficulty: u8,
+}
+
+fn default_difficulty() -> u8 {
+    1
+}
+
+impl DataResource for ChunksResource {
+    const PATH: &'static str = "data/chunks.ron";
+
+    // the spawned chunks are kept
+    fn reload(&mut self, new: Self) {
+        self.prelude_chunks = new.prelude_chunks;
+        self.chunks = new.chunks;
+    }
+}
+
+impl Chunk {
+    pub fn can_be_cleared(&self, cheat_codes: &CheatCodeResource) -> bool {
+        self.required_cheats
+            .iter()
+            .all(|kind| cheat_codes.is_code_activated(kind))
+    }
+
+    fn uses_cheat(&self, kind: &CheatCodeKind) -> bool {
+        self.required_cheats.contains(kind) || self.rewarding_cheats.contains(kind)
+    }
+
+    fn fits_biome(&self, biome: Option<&BiomeData>) -> bool {
+        match biome {
+            Some(biome) => self.biomes.is_empty() || self.biomes.contains(&biome.name),
+            None => true,
+        }
+    }
+
+    /// Rightmost point of the chunk, relative to its origin
+    pub fn right_edge(&self, platform_kinds: &platform::PlatformKindsResource) -> f32 {
+        let surfaces = reachability::chunk_surfaces(self, platform_kinds).unwrap_or_default();
+
+        surfaces
+            .iter()
+            .map(|surface| surface.right)
+            .chain(self.enemies.iter().map(|enemy_data| enemy_data.position.x))
+            .chain(
+                self.hazards
+                    .iter()
+                    .map(|hazard_data| hazard_data.position.x + hazard_data.size.x / 2.0),
+            )
+            .chain(self.terminals.iter().map(|position| position.x))
+            .chain(
+                self.chars
+                    .iter()
+                    .flat_map(|ch_data| ch_data.positions.iter().map(|position| position.x)),
+            )
+            .fold(self.next_chunk_offset, f32::max)
+    }
+}
+
+// how many of the last activated cheats count as "newly bought"
+const RECENT_CHEATS_COUNT: usize = 2;
+// extra weight given to chunks using a newly bought cheat
+const RECENT_CHEAT_WEIGHT: f32 = 3.0;
+
+/// Picks a chunk of the biome the player can clear, following the difficulty
+/// curve and favoring the ones that use newly bought cheats
+pub fn pick_chunk<'a>(
+    chunks: &'a [Chunk],
+    cheat_codes: &CheatCodeResource,
+    difficulty: &DifficultyPoint,
+    biome: Option<&BiomeData>,
+) -> Option<&'a Chunk> {
+    let recent_cheats = cheat_codes
+        .activated_codes()
+        .iter()
+        .rev()
+        .take(RECENT_CHEATS_COUNT)
+        .collect::<Vec<&CheatCodeKind>>();
+
+    let candidates = chunks
+        .iter()
+        .filter(|chunk| chunk.can_be_cleared(cheat_codes) && chunk.fits_biome(biome))
+        .collect::<Vec<&Chunk>>();
+
+    let picked = candidates.choose_weighted(&mut rand::thread_rng(), |chunk| {
+        let recent_uses = recent_cheats
+            .iter()
+            .filter(|kind| chunk.uses_cheat(kind))
+            .count();
+        difficulty.weight(chunk.difficulty) * (1.0 + recent_uses as f32 * RECENT_CHEAT_WEIGHT)
+    });
+
+    match picked {
+        Ok(chunk) => Some(*chunk),
+        // none of the clearable chunks fits the curve
+        Err(_) => candidates.choose(&mut rand::thread_rng()).copied(),
+    }
 }
 
 pub fn spawn_chunk(
@@ -56,83 +194,129 @@ pub fn spawn_chunk(
     asset_server: &AssetServer,
     texture_atlases: &mut Assets<TextureAtlas>,
     cheat_codes: &CheatCodeResource,
+    platform_kinds: &platform::PlatformKindsResource,
+    enemy_kinds: &enemies::EnemyKindsResource,
+    letter_rate: f32,
+    enemy_rate: f32,
+    letter_director: &mut LetterDirector,
+    run_seed: &mut RunSeed,
+    biome: Option<&BiomeData>,
 ) {
+    // everything spawned for the chunk, despawned with it
+    let mut children = Vec::new();
+
     for platform_data in chunk.platforms.iter() {
-        platform::spawn_platform(
+        children.extend(platform::spawn_platform(
             &platform_data.platform_kind,
+            &platform_data.behavior,
             platform_data.position + Vec2::new(x_offset, 0.0),
             commands,
             rapier_config,
             asset_server,
-        )
+            platform_kinds,
+            biome,
+        ));
     }
 
     for enemy_data in chunk.enemies.iter() {
-        enemies::spawn_enemy(
+        // enemies get denser as the run goes on
+        if !rand::thread_rng().gen_bool(enemy_rate.clamp(0.0, 1.0) as f64) {
+            continue;
+        }
+
+        children.extend(enemies::spawn_enemy(
             &enemy_data.enemy_kind,
             enemy_data.position + Vec2::new(x_offset, 0.0),
             commands,
             rapier_config,
             asset_server,
-        )
+            enemy_kinds,
+        ));
+    }
+
+    for hazard_data in chunk.hazards.iter() {
+        children.push(hazards::spawn_hazard(
+            &hazard_data.hazard_kind,
+            hazard_data.position + Vec2::new(x_offset, 0.0),
+            hazard_data.size,
+            commands,
+            rapier_config,
+        ));
     }
 
     for terminal_position in chunk.terminals.iter() {
-        spawn_terminal(
+        children.push(spawn_terminal(
             commands,
             asset_server,
             texture_atlases,
             &(*terminal_position + Vec2::new(x_offset, 0.0)),
-        )
+        ));
     }
 
     // TODO: needs some refactoring
     for ch_data in &chunk.chars {
         if let Some(cheat_kind) = ch_data.cheat_kind {
             let code = cheat_codes.codes.get(&cheat_kind).unwrap();
-            let shuffled_text = match code.rarity {
-                CheatCodeRarity::Mandatory => {
-                    randomize_text(&code.text, vec![2, 3, 1, 0], ch_data.is_random)
-                }
-                CheatCodeRarity::Common => {
-                    randomize_text(&code.text, vec![2, 3, 1, 0], ch_data.is_random)
-                }
-                CheatCodeRarity::Rare => {
-                    randomize_text(&code.text, vec![2, 5, 3, 1, 0, 4], ch_data.is_random)
-                }
-                CheatCodeRarity::Legendary => {
-                    randomize_text(&code.text, vec![4, 2, 6, 3, 1, 7, 0, 5], ch_data.is_random)
+
+            // decoys only look like a code
+            if ch_data.is_random {
+                let noise = Alphanumeric
+                    .sample_string(&mut rand::thread_rng(), code.text.len())
+                    .to_lowercase();
+                for (ch_position, ch) in ch_data.positions.iter().zip(noise.chars()) {
+                    children.push(spawn_char(
+                        commands,
+                        asset_server,
+                        texture_atlases,
+                        ch,
+                        &(*ch_position + Vec2::new(x_offset, 0.0)),
+                    ));
                 }
-            };
+                continue;
+            }
 
-            for n in 0..ch_data.positions.len() {
-                let ch_position = ch_data.positions[n].clone();
-                let ch = shuffled_text.chars().nth(n).unwrap();
-                spawn_char(
+            let order = run_seed.permutation(code.text.len());
+            for (ch_position, index) in ch_data.positions.iter().zip(order) {
+                let ch = code.text.chars().nth(index).unwrap();
+                let ch_entity = spawn_char(
                     commands,
                     asset_server,
                     texture_atlases,
                     ch,
-                    &(ch_position + Vec2::new(x_offset, 0.0)),
+                    &(*ch_position + Vec2::new(x_offset, 0.0)),
                 );
+                commands.entity(ch_entity).insert(CodeFragment {
+                    kind: cheat_kind,
+                    index,
+                });
+                children.push(ch_entity);
             }
         } else {
-            let rand_chars = Alphanumeric
-                .sample_string(&mut rand::thread_rng(), ch_data.positions.len())
-                .to_lowercase();
+            // random letters get scarcer as the run goes on
+            if !rand::thread_rng().gen_bool(letter_rate.clamp(0.0, 1.0) as f64) {
+                continue;
+            }
 
-            for n in 0..rand_chars.len() {
-                let ch_position = ch_data.positions[n].clone();
-                spawn_char(
+            for ch_position in ch_data.positions.iter() {
+                children.push(spawn_char(
                     commands,
                     asset_server,
                     texture_atlases,
-                    rand_chars.chars().nth(n).unwrap(),
-                    &(ch_position + Vec2::new(x_offset, 0.0)),
-                );
+                    letter_director.pick_letter(),
+                    &(*ch_position + Vec2::new(x_offset, 0.0)),
+                ));
             }
         }
     }
+
+    // the root stays at the origin so that the children keep their world positions
+    commands
+        .spawn_bundle((Transform::identity(), GlobalTransform::identity()))
+        .insert(ChunkRoot {
+            right_x: x_offset + chunk.right_edge(platform_kinds),
+        })
+        .insert(Name::new("Chunk"))
+        .push_children(&children);
 }
 
 /// Test spawn platform
@@ -144,6 +328,11 @@ pub fn chunk_test_system(
     asset_server: Res<AssetServer>,
     mut texture_atlases: ResMut<Assets<TextureAtlas>>,
     cheat_codes: ResMut<CheatCodeResource>,
+    platform_kinds: Res<platform::PlatformKindsResource>,
+    enemy_kinds: Res<enemies::EnemyKindsResource>,
+    biomes: Res<BiomesResource>,
+    mut letter_director: ResMut<LetterDirector>,
+    mut run_seed: ResMut<RunSeed>,
 ) {
     let chunk_to_spawn = chunks_resource.prelude_chunks.get(0);
 
@@ -156,6 +345,13 @@ pub fn chunk_test_system(
             &asset_server,
             &mut texture_atlases,
             &cheat_codes,
+            &platform_kinds,
+            &enemy_kinds,
+            1.0,
+            1.0,
+            &mut letter_director,
+            &mut run_seed,
+            biomes.at(0.0),
         );
     }
 }
@@ -167,6 +363,11 @@ pub fn generate_prelude_chunk(
     mut chunks_resource: ResMut<ChunksResource>,
     mut texture_atlases: ResMut<Assets<TextureAtlas>>,
     cheat_codes: ResMut<CheatCodeResource>,
+    platform_kinds: Res<platform::PlatformKindsResource>,
+    enemy_kinds: Res<enemies::EnemyKindsResource>,
+    biomes: Res<BiomesResource>,
+    mut letter_director: ResMut<LetterDirector>,
+    mut run_seed: ResMut<RunSeed>,
 ) {
     if chunks_resource.furthest_x <= 0.0 {
         let chunk_to_spawn = chunks_resource
@@ -182,8 +383,20 @@ pub fn generate_prelude_chunk(
             &asset_server,
             &mut texture_atlases,
             &cheat_codes,
+            &platform_kinds,
+            &enemy_kinds,
+            1.0,
+            1.0,
+            &mut letter_director,
+            &mut run_seed,
+            biomes.at(0.0),
         );
         chunks_resource.furthest_x = chunk_to_spawn.next_chunk_offset;
+        // spawned at the origin, its offset is ignored
+        chunks_resource.last_chunk = Some(Chunk {
+            chunk_offset: 0.0,
+            ..chunk_to_spawn.clone()
+        });
     }
 }
 
@@ -195,6 +408,15 @@ pub fn generate_chunks(
     player_query: Query<(&runner::Player, &RigidBodyPositionComponent)>,
     mut texture_atlases: ResMut<Assets<TextureAtlas>>,
     cheat_codes: ResMut<CheatCodeResource>,
+    platform_kinds: Res<platform::PlatformKindsResource>,
+    enemy_kinds: Res<enemies::EnemyKindsResource>,
+    difficulty_curve: Res<DifficultyCurveResource>,
+    stats: Res<GameStatsResource>,
+    biomes: Res<BiomesResource>,
+    mut letter_director: ResMut<LetterDirector>,
+    mut run_seed: ResMut<RunSeed>,
+    generator_rules: Res<GeneratorRules>,
+    mut is_stuck: Local<bool>,
 ) {
     assert!(chunks_resource.furthest_x >= 0.0);
 
@@ -203,21 +425,55 @@ pub fn generate_chunks(
             < 2000.0
         {
             info!("generating next chunks");
+            let difficulty = difficulty_curve.at(stats.distance);
+
             for _ in 0..=4 {
-                // roll for "cheat chunk" (chunk that requires an acquired cheat to get past)
-                let mut cheat_chunk_roll = rand::thread_rng();
-                let chunk_to_spawn = if cheat_chunk_roll.gen_range(0..=3) == 0 {
-                    // TODO: check player's activated cheats
-                    chunks_resource
-                        .jump_chunks
-                        .choose(&mut rand::thread_rng())
-                        .unwrap()
+                let biome = biomes.at(chunks_resource.furthest_x / 100.);
+
+                let generate = || {
+                    generator::generate_chunk(
+                        &generator_rules,
+                        &cheat_codes,
+                        &difficulty,
+                        &platform_kinds,
+                        chunks_resource.last_chunk.as_ref(),
+                    )
+                };
+
+                // handcrafted chunks are used when the generation fails
+                let mut generated_chunk = if generator_rules.should_generate() {
+                    generate()
                 } else {
-                    chunks_resource
-                        .basic_chunks
-                        .choose(&mut rand::thread_rng())
-                        .unwrap()
+                    None
                 };
+                // and the generator when none of the biome can be cleared
+                if generated_chunk.is_none()
+                    && pick_chunk(&chunks_resource.chunks, &cheat_codes, &difficulty, biome)
+                        .is_none()
+                {
+                    generated_chunk = generate();
+                }
+
+                let chunk_to_spawn = match generated_chunk
+                    .as_ref()
+                    .or_else(|| {
+                        pick_chunk(&chunks_resource.chunks, &cheat_codes, &difficulty, biome)
+                    })
+                    // then the chunks of every biome
+                    .or_else(|| {
+                        pick_chunk(&chunks_resource.chunks, &cheat_codes, &difficulty, None)
+                    }) {
+                    Some(chunk) => chunk,
+                    None => {
+                        // tried again every frame, only reported once
+                        if !*is_stuck {
+                            error!("No chunk can be cleared with the activated cheats");
+                            *is_stuck = true;
+                        }
+                        return;
+                    }
+                };
+                *is_stuck = false;
 
                 spawn_chunk(
                     chunk_to_spawn,
@@ -227,67 +483,49 @@ pub fn generate_chunks(
                     &asset_server,
                     &mut texture_atlases,
                     &cheat_codes,
+                    &platform_kinds,
+                    &enemy_kinds,
+                    difficulty.letter_rate,
+                    difficulty.enemy_rate,
+                    &mut letter_director,
+                    &mut run_seed,
+                    biome,
                 );
 
                 chunks_resource.furthest_x += chunk_to_spawn.next_chunk_offset;
+                chunks_resource.last_chunk = Some(chunk_to_spawn.clone());
             }
         }
     }
 }
 
-pub fn despawn_platforms(
+pub fn despawn_chunks(
     mut commands: Commands,
-    rapier_config: Res<RapierConfiguration>,
-    player_query: Query<&RigidBodyPositionComponent, With<runner::Player>>,
-    platform_query: Query<(Entity, &RigidBodyPositionComponent), With<platform::Platform>>,
+    windows: Res<Windows>,
+    camera_query: Query<&Transform, With<TwoDCameraComponent>>,
+    chunk_query: Query<(Entity, &ChunkRoot)>,
+    checkpoint: Res<runner::Checkpoint>,
 ) {
-    for player_rb_pos in player_query.iter() {
-        for (platform_entity, platform_rb_pos) in platform_query.iter() {
-            if (player_rb_pos.position.translation.x * rapier_config.scale)
-                - (platform_rb_pos.position.translation.x * rapier_config.scale)
-                > 10000.0
-            {
-                info!("despawning platform");
-                commands.entity(platform_entity).despawn_recursive();
-            }
-        }
-    }
-}
+    let half_width = windows
+        .get_primary()
+        .map_or(0.0, |window| window.width() / 2.0);
 
-pub fn despawn_enemies(
-    mut commands: Commands,
-    rapier_config: Res<RapierConfiguration>,
-    player_query: Query<&RigidBodyPositionComponent, With<runner::Player>>,
-    enemy_query: Query<(Entity, &RigidBodyPositionComponent), With<enemies::Enemy>>,
-) {
-    for player_rb_pos in player_query.iter() {
-        for (enemy_entity, enemy_rb_pos) in enemy_query.iter() {
-            if (player_rb_pos.position.translation.x * rapier_config.scale)
-                - (enemy_rb_pos.position.translation.x * rapier_config.scale)
-                > 10000.0
+    for camera_transform in camera_query.iter() {
+        let screen_left = camera_transform.translation.x - half_width;
+
+        for (entity, chunk_root) in chunk_query.iter() {
+            // the player can still respawn in the chunks after the checkpoint
+            if chunk_root.right_x < screen_left - DESPAWN_DISTANCE
+                && !checkpoint.keeps(chunk_root.right_x)
             {
-                info!("despawning enemy");
-                commands.entity(enemy_entity).despawn_recursive();
+                commands.entity(entity).despawn_recursive();
             }
         }
     }
 }
 
-pub fn despawn_interactables(
-    mut commands: Commands,
-    rapier_config: Res<RapierConfiguration>,
-    player_query: Query<&RigidBodyPositionComponent, With<runner::Player>>,
-    interactable_query: Query<(Entity, &Transform), With<InteractableComponent>>,
-) {
-    for player_rb_pos in player_query.iter() {
-        for (interactable_entity, interactable_transform) in interactable_query.iter() {
-            if (player_rb_pos.position.translation.x * rapier_config.scale)
-                - interactable_transform.translation.x
-                > 10000.0
-            {
-                info!("despawning interactable");
-                commands.entity(interactable_entity).despawn_recursive();
-            }
-        }
+pub fn despawn_all_chunks(mut commands: Commands, chunk_query: Query<Entity, With<ChunkRoot>>) {
+    for entity in chunk_query.iter() {
+        commands.entity(entity).despawn_recursive();
     }
 }
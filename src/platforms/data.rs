@@ -0,0 +1,105 @@
+use std::marker::PhantomData;
+use std::time::Duration;
+
+use bevy::asset::{AssetLoader, LoadContext, LoadedAsset};
+use bevy::prelude::*;
+use bevy::reflect::TypeUuid;
+use bevy::utils::BoxedFuture;
+use ron::de::from_bytes;
+use serde::de::DeserializeOwned;
+
+use crate::toast::ShowToast;
+
+/// Content of a file of `assets/data`, parsed into its resource by `reload_data`
+#[derive(TypeUuid)]
+#[uuid = "5f3a0c1e-8d2b-4b7e-9a61-2f4c8e7d1b30"]
+pub struct RonAsset {
+    pub bytes: Vec<u8>,
+}
+
+#[derive(Default)]
+pub struct RonAssetLoader;
+
+impl AssetLoader for RonAssetLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
+        Box::pin(async move {
+            // parsed later, so that an error doesn't discard the current data
+            load_context.set_default_asset(LoadedAsset::new(RonAsset {
+                bytes: bytes.to_vec(),
+            }));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["ron"]
+    }
+}
+
+/// Resource read from a file of `assets/data`
+pub trait DataResource: DeserializeOwned + Send + Sync + 'static {
+    /// Path of the file in the assets folder
+    const PATH: &'static str;
+
+    /// Applies a new version of the file
+    fn reload(&mut self, new: Self) {
+        *self = new;
+    }
+}
+
+/// Keeps the data file loaded, and watched in debug builds
+pub struct DataHandle<T> {
+    handle: Handle<RonAsset>,
+    marker: PhantomData<T>,
+}
+
+pub fn load_data<T: DataResource>(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(DataHandle::<T> {
+        handle: asset_server.load(T::PATH),
+        marker: PhantomData,
+    });
+}
+
+pub fn reload_data<T: DataResource>(
+    mut asset_events: EventReader<AssetEvent<RonAsset>>,
+    assets: Res<Assets<RonAsset>>,
+    data_handle: Option<Res<DataHandle<T>>>,
+    mut resource: ResMut<T>,
+    mut toast_writer: EventWriter<ShowToast>,
+) {
+    let data_handle = match data_handle {
+        Some(data_handle) => data_handle,
+        None => return,
+    };
+
+    for event in asset_events.iter() {
+        let handle = match event {
+            AssetEvent::Created { handle } | AssetEvent::Modified { handle } => handle,
+            AssetEvent::Removed { .. } => continue,
+        };
+
+        if *handle != data_handle.handle {
+            continue;
+        }
+
+        if let Some(asset) = assets.get(handle) {
+            match from_bytes::<T>(&asset.bytes) {
+                Ok(new) => {
+                    info!("[PlatformsPlugin] Loaded {}", T::PATH);
+                    resource.reload(new);
+                }
+                Err(error) => {
+                    error!("[PlatformsPlugin] Cannot load {}: {}", T::PATH, error);
+                    toast_writer.send(ShowToast {
+                        value: format!("{}: {}", T::PATH, error),
+                        duration: Duration::from_secs(5),
+                    });
+                }
+            }
+        }
+    }
+}
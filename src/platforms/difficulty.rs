@@ -0,0 +1,179 @@
+use serde::Deserialize;
+
+use super::data::DataResource;
+
+/// Difficulty of the run at a given distance
+#[derive(Deserialize, Debug, Clone)]
+pub struct DifficultyPoint {
+    // in meters
+    pub distance: f32,
+    // weight of each chunk difficulty, starting at 1
+    pub weights: Vec<f32>,
+    // chance for a random letter to be spawned
+    pub letter_rate: f32,
+    // chance for an enemy of a chunk to be spawned
+    pub enemy_rate: f32,
+    // part of the widest gap used by the generated chunks, the handcrafted gaps are fixed
+    pub gap_scale: f32,
+}
+
+impl DifficultyPoint {
+    pub fn weight(&self, difficulty: u8) -> f32 {
+        match difficulty {
+            0 => 0.0,
+            _ => self
+                .weights
+                .get(difficulty as usize - 1)
+                .copied()
+                .unwrap_or(0.0),
+        }
+    }
+
+    /// Average difficulty of the chunks, from 1 to the number of weights
+    pub fn threat_level(&self) -> f32 {
+        let total: f32 = self.weights.iter().sum();
+        if total <= 0.0 {
+            return 1.0;
+        }
+
+        self.weights
+            .iter()
+            .enumerate()
+            .map(|(i, weight)| (i + 1) as f32 * weight)
+            .sum::<f32>()
+            / total
+    }
+}
+
+/// Curve defined in `assets/data/difficulty.ron`, sorted by distance
+#[derive(Deserialize)]
+pub struct DifficultyCurveResource {
+    pub points: Vec<DifficultyPoint>,
+}
+
+impl DataResource for DifficultyCurveResource {
+    const PATH: &'static str = "data/difficulty.ron";
+}
+
+impl DifficultyCurveResource {
+    pub fn at(&self, distance: f32) -> DifficultyPoint {
+        let next_index = self
+            .points
+            .iter()
+            .position(|point| point.distance > distance);
+
+        match next_index {
+            Some(0) => self.points[0].clone(),
+            Some(i) => {
+                let from = &self.points[i - 1];
+                let to = &self.points[i];
+                let t = (distance - from.distance) / (to.distance - from.distance);
+                let len = from.weights.len().max(to.weights.len());
+
+                DifficultyPoint {
+                    distance,
+                    weights: (1..=len as u8)
+                        .map(|difficulty| lerp(from.weight(difficulty), to.weight(difficulty), t))
+                        .collect(),
+                    letter_rate: lerp(from.letter_rate, to.letter_rate, t),
+                    enemy_rate: lerp(from.enemy_rate, to.enemy_rate, t),
+                    gap_scale: lerp(from.gap_scale, to.gap_scale, t),
+                }
+            }
+            // past the last point the curve stays flat
+            None => match self.points.last() {
+                Some(last) => last.clone(),
+                None => DifficultyPoint {
+                    distance,
+                    weights: vec![1.0],
+                    letter_rate: 1.0,
+                    enemy_rate: 1.0,
+                    gap_scale: 1.0,
+                },
+            },
+        }
+    }
+}
+
+fn lerp(from: f32, to: f32, t: f32) -> f32 {
+    from + (to - from) * t
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn point(distance: f32, weights: Vec<f32>, rate: f32) -> DifficultyPoint {
+        DifficultyPoint {
+            distance,
+            weights,
+            letter_rate: rate,
+            enemy_rate: rate,
+            gap_scale: rate,
+        }
+    }
+
+    fn curve() -> DifficultyCurveResource {
+        DifficultyCurveResource {
+            points: vec![
+                point(100.0, vec![1.0], 1.0),
+                point(200.0, vec![0.0, 1.0, 0.5], 0.5),
+            ],
+        }
+    }
+
+    #[test]
+    fn curve_is_flat_before_the_first_point() {
+        let start = curve().at(0.0);
+        assert_eq!(start.weights, vec![1.0]);
+        assert_eq!(start.letter_rate, 1.0);
+    }
+
+    #[test]
+    fn curve_is_flat_past_the_last_point() {
+        let end = curve().at(1000.0);
+        assert_eq!(end.weights, vec![0.0, 1.0, 0.5]);
+        assert_eq!(end.gap_scale, 0.5);
+    }
+
+    #[test]
+    fn curve_interpolates_between_points() {
+        let middle = curve().at(150.0);
+        // the missing weights count as 0
+        assert_eq!(middle.weights, vec![0.5, 0.5, 0.25]);
+        assert_eq!(middle.letter_rate, 0.75);
+        assert_eq!(middle.enemy_rate, 0.75);
+    }
+
+    #[test]
+    fn curve_matches_its_points_at_the_edges() {
+        assert_eq!(curve().at(100.0).weights, vec![1.0, 0.0, 0.0]);
+        assert_eq!(curve().at(200.0).weights, vec![0.0, 1.0, 0.5]);
+    }
+
+    #[test]
+    fn empty_curve_has_a_default() {
+        let curve = DifficultyCurveResource { points: vec![] };
+        assert_eq!(curve.at(50.0).weight(1), 1.0);
+    }
+
+    #[test]
+    fn threat_level_averages_the_difficulties() {
+        assert_eq!(point(0.0, vec![1.0, 1.0], 1.0).threat_level(), 1.5);
+        assert_eq!(point(0.0, vec![0.0, 0.0], 1.0).threat_level(), 1.0);
+        assert_eq!(point(0.0, vec![1.0], 1.0).weight(0), 0.0);
+        assert_eq!(point(0.0, vec![1.0], 1.0).weight(2), 0.0);
+    }
+
+    #[test]
+    fn curve_data_is_valid() {
+        let curve = ron::de::from_bytes::<DifficultyCurveResource>(include_bytes!(
+            "../../assets/data/difficulty.ron"
+        ))
+        .unwrap();
+        assert!(curve
+            .points
+            .windows(2)
+            .all(|points| points[0].distance < points[1].distance));
+    }
+}
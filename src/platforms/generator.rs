@@ -0,0 +1,487 @@
+use bevy::prelude::*;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use serde::Deserialize;
+
+use super::chunk::{CharData, Chunk, EnemyData, PlatformData};
+use super::data::DataResource;
+use super::difficulty::DifficultyPoint;
+use super::platform::{PlatformBehavior, PlatformKind, PlatformKindsResource};
+use super::reachability::{self, PlayerReach, WALKABLE_GAP};
+use crate::cheat_codes::{CheatCodeKind, CheatCodeResource};
+use crate::enemies::EnemyKind;
+use crate::runner::PLAYER_HEIGHT;
+
+// attempts at building a chunk that follows the reachability rules
+const MAX_ATTEMPTS: usize = 5;
+// same as the handcrafted chunks
+const CHUNK_OFFSET: f32 = 80.0;
+// space between the platforms of a ground run, in pixels
+const GROUND_SPACING: f32 = 2.0;
+// height of the enemies and the letters above the ground, in pixels
+const ENEMY_HEIGHT: f32 = 120.0;
+const LETTER_HEIGHT: f32 = PLAYER_HEIGHT / 2.0 + 20.0;
+// space between the letters of a trail, in pixels
+const LETTER_SPACING: f32 = 40.0;
+
+/// Rules of a chunk difficulty
+#[derive(Deserialize, Debug)]
+pub struct DifficultyRules {
+    // part of the reach of the player used by the gaps and the steps
+    pub gap_ratio: f32,
+    pub step_ratio: f32,
+    // chance for a ground run to have an enemy or a letter trail
+    pub enemy_rate: f32,
+    pub letter_trail_rate: f32,
+}
+
+/// Rules defined in `assets/data/generator.ron`
+#[derive(Deserialize)]
+pub struct GeneratorRules {
+    // chance for a chunk to be generated instead of picked from the handcrafted ones
+    pub mix_ratio: f32,
+    pub ground_kind: PlatformKind,
+    pub step_kind: PlatformKind,
+    pub ground_y: f32,
+    // one of them is picked for every enemy
+    pub enemy_kinds: Vec<EnemyKind>,
+    pub min_features: usize,
+    pub max_features: usize,
+    // from the difficulty 1
+    pub difficulties: Vec<DifficultyRules>,
+}
+
+impl DataResource for GeneratorRules {
+    const PATH: &'static str = "data/generator.ron";
+}
+
+impl GeneratorRules {
+    pub fn should_generate(&self) -> bool {
+        rand::thread_rng().gen_bool(self.mix_ratio.clamp(0.0, 1.0) as f64)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Feature {
+    GroundRun,
+    Gap,
+    Staircase,
+}
+
+/// Chunk under construction, from left to right
+struct ChunkBuilder<'a> {
+    chunk: Chunk,
+    rules: &'a GeneratorRules,
+    difficulty_rules: &'a DifficultyRules,
+    difficulty: &'a DifficultyPoint,
+    reach: PlayerReach,
+    ground_half_extents: Vec2,
+    step_half_extents: Vec2,
+    // right edge of the last platform
+    right: f32,
+}
+
+impl<'a> ChunkBuilder<'a> {
+    fn ground_top(&self) -> f32 {
+        self.rules.ground_y + self.ground_half_extents.y
+    }
+
+    fn add_platform(&mut self, platform_kind: &PlatformKind, position: Vec2) {
+        self.chunk.platforms.push(PlatformData {
+            platform_kind: platform_kind.clone(),
+            position,
+            behavior: PlatformBehavior::Static,
+        });
+    }
+
+    fn add_letter_trail(&mut self, center: Vec2) {
+        let positions = [-LETTER_SPACING, 0.0, LETTER_SPACING]
+            .iter()
+            .map(|dx| center + Vec2::new(*dx, LETTER_HEIGHT))
+            .collect();
+
+        self.chunk.chars.push(CharData {
+            cheat_kind: None,
+            positions,
+            is_random: true,
+        });
+    }
+
+    fn ground_run(&mut self, length: usize, with_extras: bool) {
+        let mut rng = rand::thread_rng();
+        let rules = self.rules;
+        let half_extents = self.ground_half_extents;
+
+        for i in 0..length {
+            let position = Vec2::new(
+                self.right + half_extents.x + if i == 0 { 0.0 } else { GROUND_SPACING },
+                rules.ground_y,
+            );
+            self.add_platform(&rules.ground_kind, position);
+            self.right = position.x + half_extents.x;
+
+            if !with_extras {
+                continue;
+            }
+
+            let top = Vec2::new(position.x, self.ground_top());
+            let enemy_kind = rules.enemy_kinds.choose(&mut rng).filter(|_| {
+                let enemy_rate = self.difficulty_rules.enemy_rate * self.difficulty.enemy_rate;
+                rng.gen_bool(enemy_rate.clamp(0.0, 1.0) as f64)
+            });
+            if let Some(enemy_kind) = enemy_kind {
+                self.chunk.enemies.push(EnemyData {
+                    enemy_kind: enemy_kind.clone(),
+                    position: top + Vec2::new(0.0, ENEMY_HEIGHT),
+                });
+            } else if rng.gen_bool(self.difficulty_rules.letter_trail_rate.clamp(0.0, 1.0) as f64) {
+                self.add_letter_trail(top);
+            }
+        }
+    }
+
+    fn gap(&mut self) {
+        // the gaps widen as the run goes on
+        let max_gap = self.reach.max_gap()
+            * self.difficulty_rules.gap_ratio
+            * self.difficulty.gap_scale.clamp(0.0, 1.0);
+        if max_gap <= WALKABLE_GAP {
+            self.ground_run(1, true);
+            return;
+        }
+
+        self.right += rand::thread_rng().gen_range(WALKABLE_GAP..=max_gap);
+        self.ground_run(2, true);
+    }
+
+    fn staircase(&mut self) {
+        let mut rng = rand::thread_rng();
+
+        // steps hidden above the head of the player would be skipped
+        let step_height = (self.reach.max_step() * self.difficulty_rules.step_ratio)
+            .min(PLAYER_HEIGHT - self.step_half_extents.y * 2.0);
+        if step_height <= 0.0 || self.reach.jump_height <= 0.0 {
+            self.ground_run(1, true);
+            return;
+        }
+
+        let rules = self.rules;
+        let half_extents = self.step_half_extents;
+        let mut top = self.ground_top();
+        for _ in 0..rng.gen_range(2..=4) {
+            top += step_height;
+            let position = Vec2::new(
+                self.right + rng.gen_range(0.0..=WALKABLE_GAP) + half_extents.x,
+                top - half_extents.y,
+            );
+            self.add_platform(&rules.step_kind, position);
+            self.right = position.x + half_extents.x;
+
+            if rng.gen_bool(self.difficulty_rules.letter_trail_rate.clamp(0.0, 1.0) as f64) {
+                self.chunk.chars.push(CharData {
+                    cheat_kind: None,
+                    positions: vec![Vec2::new(position.x, top + LETTER_HEIGHT)],
+                    is_random: true,
+                });
+            }
+        }
+
+        // back down to the ground
+        self.ground_run(2, false);
+    }
+
+    fn build(mut self) -> Chunk {
+        let mut rng = rand::thread_rng();
+
+        // the chunk starts with a ground platform at its origin
+        self.right = -self.ground_half_extents.x;
+        self.ground_run(2, false);
+
+        let features = [Feature::GroundRun, Feature::Gap, Feature::Staircase];
+        let feature_count = rng.gen_range(
+            self.rules.min_features..=self.rules.max_features.max(self.rules.min_features),
+        );
+        for _ in 0..feature_count {
+            match features.choose(&mut rng).unwrap() {
+                Feature::GroundRun => self.ground_run(rng.gen_range(1..=3), true),
+                Feature::Gap => self.gap(),
+                Feature::Staircase => self.staircase(),
+            }
+        }
+
+        // the next chunk usually starts with a ground platform at its origin
+        self.chunk.next_chunk_offset = self.right + self.ground_half_extents.x;
+        self.chunk
+    }
+}
+
+// movement cheats the chunks are built around, from the most common to the rarest
+const MOVEMENT_CHEATS: [&[CheatCodeKind]; 5] = [
+    &[],
+    &[CheatCodeKind::Jump],
+    &[CheatCodeKind::Jump, CheatCodeKind::Dash],
+    &[CheatCodeKind::Jump, CheatCodeKind::DoubleJump],
+    &[
+        CheatCodeKind::Jump,
+        CheatCodeKind::DoubleJump,
+        CheatCodeKind::Dash,
+    ],
+];
+
+/// Builds a chunk for the current cheats of the player, none if no attempt
+/// passes the reachability rules, from the previous chunk to the next one
+pub fn generate_chunk(
+    rules: &GeneratorRules,
+    cheat_codes: &CheatCodeResource,
+    difficulty: &DifficultyPoint,
+    platform_kinds: &PlatformKindsResource,
+    previous: Option<&Chunk>,
+) -> Option<Chunk> {
+    let mut rng = rand::thread_rng();
+
+    let ground_half_extents = platform_kinds.kinds.get(&rules.ground_kind)?.half_extents;
+    let step_half_extents = platform_kinds.kinds.get(&rules.step_kind)?.half_extents;
+
+    if rules.difficulties.is_empty() {
+        return None;
+    }
+    let levels = (1..=rules.difficulties.len() as u8).collect::<Vec<u8>>();
+    let level = match levels.choose_weighted(&mut rng, |level| difficulty.weight(*level)) {
+        Ok(level) => *level,
+        // none of the difficulties fits the curve
+        Err(_) => 1,
+    };
+    let difficulty_rules = &rules.difficulties[level as usize - 1];
+
+    // the cheats usable in the chunk, the least needed ones are required
+    let movement_cheats = MOVEMENT_CHEATS
+        .iter()
+        .filter(|cheats| {
+            cheats
+                .iter()
+                .all(|kind| cheat_codes.is_code_activated(kind))
+        })
+        .collect::<Vec<_>>();
+    let reach = PlayerReach::new(movement_cheats.last()?);
+    // the next chunk is not picked yet, the usual start of a chunk stands in for it
+    let next = next_chunk_start(rules);
+
+    for _ in 0..MAX_ATTEMPTS {
+        let mut chunk = ChunkBuilder {
+            chunk: Chunk {
+                platforms: Vec::new(),
+                enemies: Vec::new(),
+                hazards: Vec::new(),
+                terminals: Vec::new(),
+                chars: Vec::new(),
+                required_cheats: Vec::new(),
+                rewarding_cheats: Vec::new(),
+                biomes: Vec::new(),
+                next_chunk_offset: 0.0,
+                chunk_offset: CHUNK_OFFSET,
+                difficulty: level,
+            },
+            rules,
+            difficulty_rules,
+            difficulty,
+            reach,
+            ground_half_extents,
+            step_half_extents,
+            right: 0.0,
+        }
+        .build();
+
+        for cheats in movement_cheats.iter() {
+            chunk.required_cheats = cheats.to_vec();
+            let is_reachable = reachability::check_chunk(&chunk, platform_kinds, cheat_codes)
+                .is_empty()
+                && previous.map_or(true, |previous| {
+                    reachability::check_transition(previous, &chunk, platform_kinds).is_none()
+                })
+                && reachability::check_transition(&chunk, &next, platform_kinds).is_none();
+            if is_reachable {
+                return Some(chunk);
+            }
+        }
+    }
+
+    warn!("No generated chunk passed the reachability rules");
+    None
+}
+
+// a ground platform at the origin, as the generated chunks and most handcrafted ones start
+fn next_chunk_start(rules: &GeneratorRules) -> Chunk {
+    Chunk {
+        platforms: vec![PlatformData {
+            platform_kind: rules.ground_kind.clone(),
+            position: Vec2::new(0.0, rules.ground_y),
+            behavior: PlatformBehavior::Static,
+        }],
+        enemies: Vec::new(),
+        hazards: Vec::new(),
+        terminals: Vec::new(),
+        chars: Vec::new(),
+        required_cheats: Vec::new(),
+        rewarding_cheats: Vec::new(),
+        biomes: Vec::new(),
+        next_chunk_offset: 0.0,
+        chunk_offset: CHUNK_OFFSET,
+        difficulty: 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ron::de::from_bytes;
+
+    use super::*;
+
+    fn rules() -> GeneratorRules {
+        from_bytes(include_bytes!("../../assets/data/generator.ron")).unwrap()
+    }
+
+    fn platform_kinds() -> PlatformKindsResource {
+        from_bytes(include_bytes!("../../assets/data/platforms.ron")).unwrap()
+    }
+
+    fn difficulty(enemy_rate: f32) -> DifficultyPoint {
+        DifficultyPoint {
+            distance: 0.0,
+            weights: vec![1.0],
+            letter_rate: 1.0,
+            enemy_rate,
+            gap_scale: 1.0,
+        }
+    }
+
+    fn build(
+        rules: &GeneratorRules,
+        level: usize,
+        difficulty: &DifficultyPoint,
+        reach: PlayerReach,
+    ) -> Chunk {
+        let platform_kinds = platform_kinds();
+        ChunkBuilder {
+            chunk: Chunk {
+                platforms: Vec::new(),
+                enemies: Vec::new(),
+                hazards: Vec::new(),
+                terminals: Vec::new(),
+                chars: Vec::new(),
+                required_cheats: Vec::new(),
+                rewarding_cheats: Vec::new(),
+                biomes: Vec::new(),
+                next_chunk_offset: 0.0,
+                chunk_offset: CHUNK_OFFSET,
+                difficulty: level as u8 + 1,
+            },
+            rules,
+            difficulty_rules: &rules.difficulties[level],
+            difficulty,
+            reach,
+            ground_half_extents: platform_kinds.kinds[&rules.ground_kind].half_extents,
+            step_half_extents: platform_kinds.kinds[&rules.step_kind].half_extents,
+            right: 0.0,
+        }
+        .build()
+    }
+
+    #[test]
+    fn chunk_starts_and_ends_on_the_ground() {
+        let rules = rules();
+        let half_extents = platform_kinds().kinds[&rules.ground_kind].half_extents;
+        let reach = PlayerReach::new(&[CheatCodeKind::Jump]);
+
+        for level in 0..rules.difficulties.len() {
+            let chunk = build(&rules, level, &difficulty(1.0), reach);
+
+            let first = chunk.platforms.first().unwrap();
+            assert_eq!(first.platform_kind, rules.ground_kind);
+            assert_eq!(first.position, Vec2::new(0.0, rules.ground_y));
+
+            let last = chunk.platforms.last().unwrap();
+            assert_eq!(last.platform_kind, rules.ground_kind);
+            assert_eq!(
+                chunk.next_chunk_offset,
+                last.position.x + half_extents.x + half_extents.x
+            );
+        }
+    }
+
+    #[test]
+    fn chunk_without_cheats_can_be_walked() {
+        let rules = rules();
+        let platform_kinds = platform_kinds();
+        let reach = PlayerReach::new(&[]);
+
+        for level in 0..rules.difficulties.len() {
+            for _ in 0..10 {
+                let chunk = build(&rules, level, &difficulty(1.0), reach);
+                let surfaces = reachability::chunk_surfaces(&chunk, &platform_kinds).unwrap();
+                let (errors, exit) = reachability::walk_chunk(&surfaces, &reach);
+                assert!(errors.is_empty(), "{:?}", errors);
+                assert!(exit.is_some());
+            }
+        }
+    }
+
+    #[test]
+    fn difficulty_without_enemies_spawns_none() {
+        let rules = rules();
+        let reach = PlayerReach::new(&[CheatCodeKind::Jump]);
+
+        for level in 0..rules.difficulties.len() {
+            assert!(build(&rules, level, &difficulty(0.0), reach)
+                .enemies
+                .is_empty());
+        }
+    }
+
+    #[test]
+    fn generated_chunk_passes_the_reachability_rules() {
+        let rules = rules();
+        let platform_kinds = platform_kinds();
+        let cheat_codes = CheatCodeResource::new();
+
+        let chunk = generate_chunk(
+            &rules,
+            &cheat_codes,
+            &difficulty(1.0),
+            &platform_kinds,
+            None,
+        )
+        .unwrap();
+        assert!(reachability::check_chunk(&chunk, &platform_kinds, &cheat_codes).is_empty());
+
+        // the same chunk can follow itself
+        let next = generate_chunk(
+            &rules,
+            &cheat_codes,
+            &difficulty(1.0),
+            &platform_kinds,
+            Some(&chunk),
+        )
+        .unwrap();
+        assert!(reachability::check_transition(&chunk, &next, &platform_kinds).is_none());
+    }
+
+    #[test]
+    fn jump_opens_gaps_wider_than_a_step() {
+        let rules = rules();
+        let platform_kinds = platform_kinds();
+        let reach = PlayerReach::new(&[CheatCodeKind::Jump]);
+        assert!(reach.max_gap() * rules.difficulties[0].gap_ratio > WALKABLE_GAP);
+
+        let has_gap = (0..50).any(|_| {
+            let chunk = build(&rules, 0, &difficulty(1.0), reach);
+            let surfaces = reachability::chunk_surfaces(&chunk, &platform_kinds).unwrap();
+            let mut right = surfaces[0].right;
+            surfaces.iter().skip(1).any(|surface| {
+                let gap = surface.left - right;
+                right = right.max(surface.right);
+                gap > WALKABLE_GAP
+            })
+        });
+        assert!(has_gap);
+    }
+}
@@ -3,27 +3,70 @@ use bevy::prelude::*;
 use ron::de::from_bytes;
 pub struct PlatformsPlugin;
 
-mod chunk;
+pub mod chunk;
+pub mod data;
+pub mod difficulty;
+pub mod generator;
 pub mod platform;
+pub mod reachability;
 
 impl Plugin for PlatformsPlugin {
     fn build(&self, app: &mut App) {
-        app.insert_resource(
-            from_bytes::<chunk::ChunksResource>(include_bytes!("../../data/chunks.ron")).unwrap(),
-        )
-        .add_system_set(
-            SystemSet::on_enter(GameStates::Main).with_system(
-                chunk::generate_prelude_chunk
-                    .after("setup_physics")
-                    .label("generate_prelude_chunk"),
-            ),
-        )
-        .add_system_set(
-            SystemSet::on_update(GameStates::Main)
-                .with_system(chunk::generate_chunks.after("generate_prelude_chunk"))
-                .with_system(chunk::despawn_platforms.after("generate_prelude_chunk"))
-                .with_system(chunk::despawn_enemies.after("generate_prelude_chunk"))
-                .with_system(chunk::despawn_interactables.after("generate_prelude_chunk")),
-        );
+        // the compiled-in data is replaced once the files of assets/data are loaded
+        app.add_asset::<data::RonAsset>()
+            .init_asset_loader::<data::RonAssetLoader>()
+            .insert_resource(
+                from_bytes::<chunk::ChunksResource>(include_bytes!("../../assets/data/chunks.ron"))
+                    .unwrap(),
+            )
+            .insert_resource(
+                from_bytes::<platform::PlatformKindsResource>(include_bytes!(
+                    "../../assets/data/platforms.ron"
+                ))
+                .unwrap(),
+            )
+            .insert_resource(
+                from_bytes::<difficulty::DifficultyCurveResource>(include_bytes!(
+                    "../../assets/data/difficulty.ron"
+                ))
+                .unwrap(),
+            )
+            .insert_resource(
+                from_bytes::<generator::GeneratorRules>(include_bytes!(
+                    "../../assets/data/generator.ron"
+                ))
+                .unwrap(),
+            )
+            .add_startup_system(data::load_data::<chunk::ChunksResource>)
+            .add_startup_system(data::load_data::<platform::PlatformKindsResource>)
+            .add_startup_system(data::load_data::<difficulty::DifficultyCurveResource>)
+            .add_startup_system(data::load_data::<generator::GeneratorRules>)
+            .add_system(data::reload_data::<chunk::ChunksResource>)
+            .add_system(data::reload_data::<platform::PlatformKindsResource>)
+            .add_system(data::reload_data::<difficulty::DifficultyCurveResource>)
+            .add_system(data::reload_data::<generator::GeneratorRules>)
+            .add_system_set(
+                SystemSet::on_enter(GameStates::Main).with_system(
+                    chunk::generate_prelude_chunk
+                        .after("setup_physics")
+                        .label("generate_prelude_chunk"),
+                ),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameStates::Main)
+                    .with_system(chunk::generate_chunks.after("generate_prelude_chunk"))
+                    .with_system(chunk::despawn_chunks.after("generate_prelude_chunk"))
+                    .with_system(platform::move_platforms)
+                    .with_system(platform::carry_player.after("player_feet"))
+                    .with_system(platform::crumble_platforms.after("player_feet"))
+                    .with_system(
+                        platform::update_one_way_platforms
+                            .after("player_feet")
+                            .before("move_character"),
+                    ),
+            )
+            .add_system_set(
+                SystemSet::on_exit(GameStates::Main).with_system(chunk::despawn_all_chunks),
+            );
     }
 }
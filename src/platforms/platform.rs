@@ -1,18 +1,98 @@
+use std::collections::HashMap;
+
 use bevy::prelude::*;
 use bevy_rapier2d::prelude::*;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+
+use super::data::DataResource;
+use crate::biomes::BiomeData;
+use crate::physics::PLAYER_GROUP;
+use crate::runner::{Player, PLAYER_HEIGHT};
+
+// the player stands on a one-way platform when its feet are this close to the top
+const ONE_WAY_TOLERANCE: f32 = 5.0;
 
 #[derive(Debug, Component)]
 pub struct Platform;
 
-#[derive(Deserialize, Debug, Hash, PartialEq, Eq, Clone)]
-pub enum PlatformKind {
-    Platform1,
-    Platform2,
-    Platform3,
-    Platform4,
-    Platform5,
-    Platform6,
+/// Free-form tags of a platform kind (for example "ground")
+#[derive(Debug, Component)]
+pub struct PlatformTags(pub Vec<String>);
+
+/// Name of a platform kind defined in `assets/data/platforms.ron`
+#[derive(Deserialize, Serialize, Debug, Hash, PartialEq, Eq, Clone)]
+#[serde(transparent)]
+pub struct PlatformKind(pub String);
+
+#[derive(Deserialize, Debug)]
+pub struct PlatformKindData {
+    // in pixels
+    pub half_extents: Vec2,
+    pub sprite: String,
+    pub sprite_offset: Vec2,
+    #[serde(default)]
+    pub friction: f32,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+/// How a platform placed in `assets/data/chunks.ron` behaves
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub enum PlatformBehavior {
+    Static,
+    /// Loops through the waypoints, relative to its position, carrying the player
+    Moving {
+        // in pixels
+        waypoints: Vec<Vec2>,
+        // in pixels per second
+        speed: f32,
+    },
+    /// Falls apart some seconds after the player stepped on it
+    Crumbling {
+        delay: f32,
+    },
+    /// Can be jumped through from below
+    OneWay,
+}
+
+impl PlatformBehavior {
+    pub fn is_static(&self) -> bool {
+        matches!(self, PlatformBehavior::Static)
+    }
+}
+
+impl Default for PlatformBehavior {
+    fn default() -> Self {
+        PlatformBehavior::Static
+    }
+}
+
+#[derive(Debug, Component)]
+pub struct MovingPlatform {
+    // absolute positions, in pixels
+    waypoints: Vec<Vec2>,
+    speed: f32,
+    next_waypoint: usize,
+}
+
+#[derive(Debug, Component)]
+pub struct CrumblingPlatform {
+    timer: Timer,
+    triggered: bool,
+}
+
+#[derive(Debug, Component)]
+pub struct OneWayPlatform {
+    half_height: f32,
+}
+
+#[derive(Deserialize)]
+pub struct PlatformKindsResource {
+    pub kinds: HashMap<PlatformKind, PlatformKindData>,
+}
+
+impl DataResource for PlatformKindsResource {
+    const PATH: &'static str = "data/platforms.ron";
 }
 
 /// Test spawn platform
@@ -21,69 +101,67 @@ pub fn spawn_test_platforms(
     mut commands: Commands,
     rapier_config: Res<RapierConfiguration>,
     asset_server: Res<AssetServer>,
+    platform_kinds: Res<PlatformKindsResource>,
 ) {
     spawn_platform(
-        &PlatformKind::Platform1,
+        &PlatformKind("Platform1".to_string()),
+        &PlatformBehavior::Static,
         [0.0, -290.0].into(),
         &mut commands,
         &rapier_config,
         &asset_server,
+        &platform_kinds,
+        None,
     );
 }
 
 /// Spawn an individual platform at a location
 pub fn spawn_platform(
     platform_kind: &PlatformKind,
+    behavior: &PlatformBehavior,
     position: Vec2,
     commands: &mut Commands,
     rapier_config: &RapierConfiguration,
     asset_server: &AssetServer,
-) {
-    match platform_kind {
-        PlatformKind::Platform1 => {
-            spawn_platform_1(position, commands, rapier_config, asset_server);
-        }
-        PlatformKind::Platform2 => {
-            spawn_platform_2(position, commands, rapier_config, asset_server);
-        }
-        PlatformKind::Platform3 => {
-            spawn_platform_3(position, commands, rapier_config, asset_server);
-        }
-        PlatformKind::Platform4 => {
-            spawn_platform_4(position, commands, rapier_config, asset_server);
+    platform_kinds: &PlatformKindsResource,
+    biome: Option<&BiomeData>,
+) -> Option<Entity> {
+    let platform_data = match platform_kinds.kinds.get(platform_kind) {
+        Some(platform_data) => platform_data,
+        None => {
+            error!("Unknown platform kind: {:?}", platform_kind);
+            return None;
         }
-        PlatformKind::Platform5 => {
-            spawn_platform_5(position, commands, rapier_config, asset_server);
-        }
-        PlatformKind::Platform6 => {
-            spawn_platform_6(position, commands, rapier_config, asset_server);
-        }
-    }
-}
+    };
 
-fn spawn_platform_1(
-    position: Vec2,
-    commands: &mut Commands,
-    rapier_config: &RapierConfiguration,
-    asset_server: &AssetServer,
-) {
-    let collider_size_hx = 158.0 / rapier_config.scale / 2.0;
-    let collider_size_hy = 34.0 / rapier_config.scale / 2.0;
-
-    commands
-        .spawn_bundle(RigidBodyBundle {
-            body_type: RigidBodyType::Static.into(),
-            position: Vec2::new(
-                position.x / rapier_config.scale,
-                position.y / rapier_config.scale,
-            )
-            .into(),
-            ..Default::default()
-        })
+    let sprite = biome
+        .and_then(|biome| biome.platform_sprites.get(platform_kind))
+        .unwrap_or(&platform_data.sprite)
+        .as_str();
+
+    let collider_size_hx = platform_data.half_extents.x / rapier_config.scale;
+    let collider_size_hy = platform_data.half_extents.y / rapier_config.scale;
+
+    let body_type = match behavior {
+        PlatformBehavior::Moving { .. } => RigidBodyType::KinematicVelocityBased,
+        _ => RigidBodyType::Static,
+    };
+
+    let mut platform = commands.spawn_bundle(RigidBodyBundle {
+        body_type: body_type.into(),
+        position: Vec2::new(
+            position.x / rapier_config.scale,
+            position.y / rapier_config.scale,
+        )
+        .into(),
+        ..Default::default()
+    });
+
+    platform
         .insert_bundle(ColliderBundle {
             shape: ColliderShape::cuboid(collider_size_hx, collider_size_hy).into(),
             material: ColliderMaterial {
-                friction: 0.0,
+                friction: platform_data.friction,
                 restitution: 0.0,
                 ..Default::default()
             }
@@ -92,217 +170,183 @@ fn spawn_platform_1(
         })
         .insert(ColliderPositionSync::Discrete)
         .insert(Platform)
-        .insert(Name::new("Platform 1"))
+        .insert(Name::new(platform_kind.0.clone()))
         .with_children(|parent| {
             parent.spawn_bundle(SpriteBundle {
-                texture: asset_server.load("platform_1.png"),
-                transform: Transform::from_translation(Vec3::new(0.0, -23.0, 50.0)),
+                sprite: Sprite {
+                    color: biome.map_or(Color::WHITE, |biome| biome.tint_color()),
+                    ..Default::default()
+                },
+                texture: asset_server.load(sprite),
+                transform: Transform::from_translation(platform_data.sprite_offset.extend(50.0)),
                 ..Default::default()
             });
         });
-}
 
-fn spawn_platform_2(
-    position: Vec2,
-    commands: &mut Commands,
-    rapier_config: &RapierConfiguration,
-    asset_server: &AssetServer,
-) {
-    let collider_size_hx = 62.0 / rapier_config.scale / 2.0;
-    let collider_size_hy = 34.0 / rapier_config.scale / 2.0;
-
-    commands
-        .spawn_bundle(RigidBodyBundle {
-            body_type: RigidBodyType::Static.into(),
-            position: Vec2::new(
-                position.x / rapier_config.scale,
-                position.y / rapier_config.scale,
-            )
-            .into(),
-            ..Default::default()
-        })
-        .insert_bundle(ColliderBundle {
-            shape: ColliderShape::cuboid(collider_size_hx, collider_size_hy).into(),
-            material: ColliderMaterial {
-                friction: 0.0,
-                restitution: 0.0,
-                ..Default::default()
-            }
-            .into(),
-            ..Default::default()
-        })
-        .insert(ColliderPositionSync::Discrete)
-        .insert(Platform)
-        .insert(Name::new("Platform 2"))
-        .with_children(|parent| {
-            parent.spawn_bundle(SpriteBundle {
-                texture: asset_server.load("platform_2.png"),
-                transform: Transform::from_translation(Vec3::new(0.0, 4.0, 50.0)),
-                ..Default::default()
+    if !platform_data.tags.is_empty() {
+        platform.insert(PlatformTags(platform_data.tags.clone()));
+    }
+
+    match behavior {
+        PlatformBehavior::Static => {}
+        PlatformBehavior::Moving { waypoints, speed } => {
+            platform.insert(MovingPlatform {
+                waypoints: std::iter::once(position)
+                    .chain(waypoints.iter().map(|waypoint| position + *waypoint))
+                    .collect(),
+                speed: *speed,
+                next_waypoint: 1,
             });
-        });
+        }
+        PlatformBehavior::Crumbling { delay } => {
+            platform.insert(CrumblingPlatform {
+                timer: Timer::from_seconds(*delay, false),
+                triggered: false,
+            });
+        }
+        PlatformBehavior::OneWay => {
+            platform.insert(OneWayPlatform {
+                half_height: platform_data.half_extents.y,
+            });
+        }
+    }
+
+    Some(platform.id())
 }
 
-fn spawn_platform_3(
-    position: Vec2,
-    commands: &mut Commands,
-    rapier_config: &RapierConfiguration,
-    asset_server: &AssetServer,
+pub fn move_platforms(
+    time: Res<Time>,
+    rapier_config: Res<RapierConfiguration>,
+    mut platform_query: Query<(
+        &mut MovingPlatform,
+        &RigidBodyPositionComponent,
+        &mut RigidBodyVelocityComponent,
+    )>,
 ) {
-    let collider_size_hx = 62.0 / rapier_config.scale / 2.0;
-    let collider_size_hy = 34.0 / rapier_config.scale / 2.0;
-
-    commands
-        .spawn_bundle(RigidBodyBundle {
-            body_type: RigidBodyType::Static.into(),
-            position: Vec2::new(
-                position.x / rapier_config.scale,
-                position.y / rapier_config.scale,
-            )
-            .into(),
-            ..Default::default()
-        })
-        .insert_bundle(ColliderBundle {
-            shape: ColliderShape::cuboid(collider_size_hx, collider_size_hy).into(),
-            material: ColliderMaterial {
-                friction: 0.0,
-                restitution: 0.0,
-                ..Default::default()
-            }
-            .into(),
-            ..Default::default()
-        })
-        .insert(ColliderPositionSync::Discrete)
-        .insert(Platform)
-        .insert(Name::new("Platform 3"))
-        .with_children(|parent| {
-            parent.spawn_bundle(SpriteBundle {
-                texture: asset_server.load("platform_3.png"),
-                transform: Transform::from_translation(Vec3::new(0.0, 4.0, 50.0)),
-                ..Default::default()
-            });
-        });
+    for (mut moving, rb_pos, mut rb_vel) in platform_query.iter_mut() {
+        if moving.waypoints.len() < 2 {
+            continue;
+        }
+
+        let position = Vec2::new(rb_pos.position.translation.x, rb_pos.position.translation.y)
+            * rapier_config.scale;
+        let mut to_target = moving.waypoints[moving.next_waypoint] - position;
+
+        // close enough, head to the next waypoint
+        if to_target.length() <= moving.speed * time.delta_seconds() {
+            moving.next_waypoint = (moving.next_waypoint + 1) % moving.waypoints.len();
+            to_target = moving.waypoints[moving.next_waypoint] - position;
+        }
+
+        let velocity = to_target.normalize_or_zero() * moving.speed / rapier_config.scale;
+        rb_vel.linvel = velocity.into();
+    }
 }
 
-fn spawn_platform_4(
-    position: Vec2,
-    commands: &mut Commands,
-    rapier_config: &RapierConfiguration,
-    asset_server: &AssetServer,
+/// Moves the player along with the moving platform under its feet
+pub fn carry_player(
+    time: Res<Time>,
+    platform_query: Query<&RigidBodyVelocityComponent, With<MovingPlatform>>,
+    mut player_query: Query<(&Player, &mut RigidBodyPositionComponent)>,
 ) {
-    let collider_size_hx = 83.0 / rapier_config.scale / 2.0;
-    let collider_size_hy = 34.0 / rapier_config.scale / 2.0;
-
-    commands
-        .spawn_bundle(RigidBodyBundle {
-            body_type: RigidBodyType::Static.into(),
-            position: Vec2::new(
-                position.x / rapier_config.scale,
-                position.y / rapier_config.scale,
-            )
-            .into(),
-            ..Default::default()
-        })
-        .insert_bundle(ColliderBundle {
-            shape: ColliderShape::cuboid(collider_size_hx, collider_size_hy).into(),
-            material: ColliderMaterial {
-                friction: 0.0,
-                restitution: 0.0,
-                ..Default::default()
-            }
-            .into(),
-            ..Default::default()
-        })
-        .insert(ColliderPositionSync::Discrete)
-        .insert(Platform)
-        .insert(Name::new("Platform 4"))
-        .with_children(|parent| {
-            parent.spawn_bundle(SpriteBundle {
-                texture: asset_server.load("platform_4.png"),
-                transform: Transform::from_translation(Vec3::new(0.0, 4.0, 50.0)),
-                ..Default::default()
-            });
-        });
+    for (player, mut rb_pos) in player_query.iter_mut() {
+        let carrier = player
+            .feet_touching_platforms
+            .platforms
+            .iter()
+            .find_map(|entity| platform_query.get(*entity).ok());
+
+        if let Some(platform_vel) = carrier {
+            rb_pos.position.translation.x += platform_vel.linvel.x * time.delta_seconds();
+            rb_pos.position.translation.y += platform_vel.linvel.y * time.delta_seconds();
+        }
+    }
 }
 
-fn spawn_platform_5(
-    position: Vec2,
-    commands: &mut Commands,
-    rapier_config: &RapierConfiguration,
-    asset_server: &AssetServer,
+pub fn crumble_platforms(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut player_query: Query<&mut Player>,
+    mut platform_query: Query<(Entity, &mut CrumblingPlatform, &Children)>,
+    mut sprite_query: Query<&mut Sprite>,
 ) {
-    let collider_size_hx = 83.0 / rapier_config.scale / 2.0;
-    let collider_size_hy = 87.0 / rapier_config.scale / 2.0;
-
-    commands
-        .spawn_bundle(RigidBodyBundle {
-            body_type: RigidBodyType::Static.into(),
-            position: Vec2::new(
-                position.x / rapier_config.scale,
-                position.y / rapier_config.scale,
-            )
-            .into(),
-            ..Default::default()
-        })
-        .insert_bundle(ColliderBundle {
-            shape: ColliderShape::cuboid(collider_size_hx, collider_size_hy).into(),
-            material: ColliderMaterial {
-                friction: 0.0,
-                restitution: 0.0,
-                ..Default::default()
+    for mut player in player_query.iter_mut() {
+        for (entity, mut crumbling, children) in platform_query.iter_mut() {
+            if !crumbling.triggered {
+                crumbling.triggered = player.feet_touching_platforms.platforms.contains(&entity);
+                continue;
             }
-            .into(),
-            ..Default::default()
-        })
-        .insert(ColliderPositionSync::Discrete)
-        .insert(Platform)
-        .insert(Name::new("Platform 5"))
-        .with_children(|parent| {
-            parent.spawn_bundle(SpriteBundle {
-                texture: asset_server.load("platform_5.png"),
-                transform: Transform::from_translation(Vec3::new(0.0, 4.0, 50.0)),
-                ..Default::default()
-            });
-        });
+
+            crumbling.timer.tick(time.delta());
+
+            // fade out until the platform falls apart
+            for child in children.iter() {
+                if let Ok(mut sprite) = sprite_query.get_mut(*child) {
+                    sprite.color.set_a(1.0 - crumbling.timer.percent());
+                }
+            }
+
+            if crumbling.timer.finished() {
+                info!("platform crumbled");
+                player
+                    .feet_touching_platforms
+                    .platforms
+                    .retain(|platform| *platform != entity);
+                commands.entity(entity).despawn_recursive();
+            }
+        }
+    }
 }
 
-fn spawn_platform_6(
-    position: Vec2,
-    commands: &mut Commands,
-    rapier_config: &RapierConfiguration,
-    asset_server: &AssetServer,
+/// Lets the player through one-way platforms unless it lands on them from above
+pub fn update_one_way_platforms(
+    rapier_config: Res<RapierConfiguration>,
+    mut player_query: Query<(
+        &mut Player,
+        &RigidBodyPositionComponent,
+        &RigidBodyVelocityComponent,
+    )>,
+    mut platform_query: Query<
+        (
+            Entity,
+            &OneWayPlatform,
+            &RigidBodyPositionComponent,
+            &mut ColliderFlagsComponent,
+        ),
+        Without<Player>,
+    >,
 ) {
-    let collider_size_hx = 135.0 / rapier_config.scale / 2.0;
-    let collider_size_hy = 87.0 / rapier_config.scale / 2.0;
-
-    commands
-        .spawn_bundle(RigidBodyBundle {
-            body_type: RigidBodyType::Static.into(),
-            position: Vec2::new(
-                position.x / rapier_config.scale,
-                position.y / rapier_config.scale,
-            )
-            .into(),
-            ..Default::default()
-        })
-        .insert_bundle(ColliderBundle {
-            shape: ColliderShape::cuboid(collider_size_hx, collider_size_hy).into(),
-            material: ColliderMaterial {
-                friction: 0.0,
-                restitution: 0.0,
-                ..Default::default()
+    for (mut player, player_rb_pos, player_rb_vel) in player_query.iter_mut() {
+        let player_bottom =
+            player_rb_pos.position.translation.y * rapier_config.scale - PLAYER_HEIGHT / 2.0;
+
+        for (entity, one_way, platform_rb_pos, mut flags) in platform_query.iter_mut() {
+            let platform_top =
+                platform_rb_pos.position.translation.y * rapier_config.scale + one_way.half_height;
+            let is_solid =
+                player_bottom >= platform_top - ONE_WAY_TOLERANCE && player_rb_vel.linvel.y <= 0.0;
+
+            let solver_groups = if is_solid {
+                InteractionGroups::all()
+            } else {
+                InteractionGroups::new(u32::MAX, u32::MAX ^ PLAYER_GROUP)
+            };
+
+            if flags.solver_groups != solver_groups {
+                flags.solver_groups = solver_groups;
             }
-            .into(),
-            ..Default::default()
-        })
-        .insert(ColliderPositionSync::Discrete)
-        .insert(Platform)
-        .insert(Name::new("Platform 6"))
-        .with_children(|parent| {
-            parent.spawn_bundle(SpriteBundle {
-                texture: asset_server.load("platform_6.png"),
-                transform: Transform::from_translation(Vec3::new(0.0, 4.0, 50.0)),
-                ..Default::default()
-            });
-        });
+
+            // the feet going up through the platform would give a jump from the ground
+            let feet = &mut player.feet_touching_platforms;
+            if is_solid {
+                if let Some(index) = feet.passing.iter().position(|x| *x == entity) {
+                    feet.passing.remove(index);
+                    feet.platforms.push(entity);
+                }
+            } else if let Some(index) = feet.platforms.iter().position(|x| *x == entity) {
+                feet.platforms.remove(index);
+                feet.passing.push(entity);
+            }
+        }
+    }
 }
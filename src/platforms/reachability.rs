@@ -0,0 +1,444 @@
+use bevy::prelude::*;
+
+use super::chunk::Chunk;
+use super::platform::{PlatformBehavior, PlatformKindsResource};
+use crate::cheat_codes::{CheatCodeKind, CheatCodeResource};
+use crate::physics::{GRAVITY, PHYSICS_SCALE};
+use crate::runner::{
+    DASH_IMPULSE, JUMP_IMPULSE, PLAYER_FEET_HEIGHT, PLAYER_HEIGHT, PLAYER_SPEED, PLAYER_WIDTH,
+};
+
+// Rules used to decide if a chunk can be cleared by the player. Every
+// distance is in pixels, relative to the chunk origin.
+
+// gaps narrower than this are crossed while running
+pub const WALKABLE_GAP: f32 = 40.0;
+// higher steps need a jump
+const STEP_TOLERANCE: f32 = 10.0;
+// only count on a part of the theoretical reach
+const REACH_MARGIN: f32 = 0.8;
+// length of the dash animation (8 frames of 0.1s)
+const DASH_DURATION: f32 = 0.8;
+// range of the letters, see spawn_char
+const CHAR_PICKUP_RANGE: f32 = 25.0;
+
+/// How far the player can go with a set of cheats
+#[derive(Debug, Clone, Copy)]
+pub struct PlayerReach {
+    pub jump_height: f32,
+    pub jump_distance: f32,
+    pub dash_distance: f32,
+}
+
+impl PlayerReach {
+    pub fn new(cheats: &[CheatCodeKind]) -> Self {
+        // the colliders have a density of 1
+        let mass =
+            PLAYER_WIDTH * (PLAYER_HEIGHT + PLAYER_FEET_HEIGHT) / (PHYSICS_SCALE * PHYSICS_SCALE);
+        let gravity = -GRAVITY;
+        let jump_velocity = JUMP_IMPULSE / mass;
+        let air_time = 2.0 * jump_velocity / gravity;
+        // same cap as in move_character
+        let run_speed = PLAYER_SPEED * PHYSICS_SCALE;
+
+        let (jump_height, jump_distance) = if cheats.contains(&CheatCodeKind::Jump) {
+            // the double jump resets the vertical velocity at the top of the first jump
+            let jumps = if cheats.contains(&CheatCodeKind::DoubleJump) {
+                2.0
+            } else {
+                1.0
+            };
+            (
+                jumps * jump_velocity.powi(2) / (2.0 * gravity),
+                jumps * air_time * run_speed,
+            )
+        } else {
+            (0.0, 0.0)
+        };
+
+        // the vertical velocity is locked while dashing
+        let dash_distance = if cheats.contains(&CheatCodeKind::Dash) {
+            (run_speed + DASH_IMPULSE / mass) * DASH_DURATION
+        } else {
+            0.0
+        };
+
+        Self {
+            jump_height: jump_height * PHYSICS_SCALE * REACH_MARGIN,
+            jump_distance: jump_distance * PHYSICS_SCALE * REACH_MARGIN,
+            dash_distance: dash_distance * PHYSICS_SCALE * REACH_MARGIN,
+        }
+    }
+
+    pub fn max_gap(&self) -> f32 {
+        (self.jump_distance + self.dash_distance).max(WALKABLE_GAP)
+    }
+
+    pub fn max_step(&self) -> f32 {
+        self.jump_height.max(STEP_TOLERANCE)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Surface {
+    pub left: f32,
+    pub right: f32,
+    pub top: f32,
+    pub bottom: f32,
+}
+
+/// Collision boxes of the platforms of a chunk, sorted from left to right
+pub fn chunk_surfaces(
+    chunk: &Chunk,
+    platform_kinds: &PlatformKindsResource,
+) -> Result<Vec<Surface>, String> {
+    let mut surfaces = Vec::new();
+
+    for platform_data in chunk.platforms.iter() {
+        let kind_data = platform_kinds
+            .kinds
+            .get(&platform_data.platform_kind)
+            .ok_or_else(|| format!("unknown platform kind {:?}", platform_data.platform_kind.0))?;
+
+        // a moving platform carries the player over its whole path
+        let (min_x, max_x) = match &platform_data.behavior {
+            PlatformBehavior::Moving { waypoints, .. } => waypoints
+                .iter()
+                .fold((0.0_f32, 0.0_f32), |(min_x, max_x), waypoint| {
+                    (min_x.min(waypoint.x), max_x.max(waypoint.x))
+                }),
+            _ => (0.0, 0.0),
+        };
+
+        surfaces.push(Surface {
+            left: platform_data.position.x + min_x - kind_data.half_extents.x,
+            right: platform_data.position.x + max_x + kind_data.half_extents.x,
+            top: platform_data.position.y + kind_data.half_extents.y,
+            bottom: platform_data.position.y - kind_data.half_extents.y,
+        });
+    }
+
+    surfaces.sort_by(|a, b| a.left.partial_cmp(&b.left).unwrap());
+    Ok(surfaces)
+}
+
+/// Where the player stands when leaving a chunk
+#[derive(Debug, Clone, Copy)]
+pub struct ChunkExit {
+    pub right: f32,
+    pub ground: f32,
+}
+
+/// Walks through the chunk from its origin and returns the obstacles out of reach
+pub fn walk_chunk(surfaces: &[Surface], reach: &PlayerReach) -> (Vec<String>, Option<ChunkExit>) {
+    let mut errors = Vec::new();
+
+    // everything behind the chunk origin is scenery
+    let mut path = surfaces.iter().filter(|surface| surface.right >= 0.0);
+
+    let first = match path.next() {
+        Some(first) => first,
+        None => return (vec!["no platform after the chunk origin".to_string()], None),
+    };
+    let mut exit = ChunkExit {
+        right: first.right,
+        ground: first.top,
+    };
+
+    for surface in path {
+        // platforms above the head of the player are optional
+        if surface.bottom >= exit.ground + PLAYER_HEIGHT {
+            continue;
+        }
+
+        if let Some(error) = check_jump(surface.left - exit.right, surface.top - exit.ground, reach)
+        {
+            errors.push(format!("platform at x={}: {}", surface.left, error));
+        }
+
+        if surface.right > exit.right {
+            exit.right = surface.right;
+            exit.ground = surface.top;
+        } else if surface.top > exit.ground {
+            exit.ground = surface.top;
+        }
+    }
+
+    (errors, Some(exit))
+}
+
+/// Checks a gap and a height step against the reach of the player
+pub fn check_jump(gap: f32, step: f32, reach: &PlayerReach) -> Option<String> {
+    if gap > reach.max_gap() {
+        Some(format!(
+            "gap of {:.0}px is wider than the reach ({:.0}px)",
+            gap,
+            reach.max_gap()
+        ))
+    } else if step > reach.max_step() {
+        Some(format!(
+            "step of {:.0}px is higher than the reach ({:.0}px)",
+            step,
+            reach.max_step()
+        ))
+    } else {
+        None
+    }
+}
+
+/// Checks that a letter can be picked up from one of the platforms
+pub fn is_char_reachable(position: Vec2, surfaces: &[Surface], reach: &PlayerReach) -> bool {
+    surfaces.iter().any(|surface| {
+        let dx = (surface.left - position.x)
+            .max(position.x - surface.right)
+            .max(0.0);
+        // the position of the player is the center of its body
+        let dy = position.y - (surface.top + PLAYER_HEIGHT / 2.0);
+
+        dx <= reach.jump_distance / 2.0 + CHAR_PICKUP_RANGE
+            && dy >= -CHAR_PICKUP_RANGE
+            && dy <= reach.jump_height + CHAR_PICKUP_RANGE
+    })
+}
+
+/// Cheats the player has while going through the chunk
+pub fn chunk_cheats(chunk: &Chunk) -> Vec<CheatCodeKind> {
+    let mut cheats = chunk.required_cheats.clone();
+
+    // the letters of a cheat next to a terminal are enough to activate it
+    if !chunk.terminals.is_empty() {
+        for ch_data in chunk.chars.iter() {
+            if let Some(kind) = ch_data.cheat_kind {
+                cheats.push(kind);
+            }
+        }
+    }
+
+    cheats
+}
+
+/// Returns every rule broken by the chunk
+pub fn check_chunk(
+    chunk: &Chunk,
+    platform_kinds: &PlatformKindsResource,
+    cheat_codes: &CheatCodeResource,
+) -> Vec<String> {
+    let mut errors = Vec::new();
+
+    for ch_data in chunk.chars.iter() {
+        if let Some(kind) = ch_data.cheat_kind {
+            match cheat_codes.codes.get(&kind) {
+                Some(code) if code.text.len() != ch_data.positions.len() => errors.push(format!(
+                    "{:?} letters: {} positions for a code of {} letters",
+                    kind,
+                    ch_data.positions.len(),
+                    code.text.len()
+                )),
+                Some(_) => {}
+                None => errors.push(format!("{:?} is not available in the game", kind)),
+            }
+        }
+    }
+
+    let surfaces = match chunk_surfaces(chunk, platform_kinds) {
+        Ok(surfaces) => surfaces,
+        Err(error) => {
+            errors.push(error);
+            return errors;
+        }
+    };
+    let reach = PlayerReach::new(&chunk_cheats(chunk));
+
+    let (path_errors, _) = walk_chunk(&surfaces, &reach);
+    errors.extend(path_errors);
+
+    for ch_data in chunk.chars.iter() {
+        for position in ch_data.positions.iter() {
+            if !is_char_reachable(*position, &surfaces, &reach) {
+                errors.push(format!(
+                    "letter at ({}, {}) is out of reach",
+                    position.x, position.y
+                ));
+            }
+        }
+    }
+
+    errors
+}
+
+/// Checks the jump between the end of a chunk and the start of the next one,
+/// the prelude chunks being spawned at the origin without their offset
+pub fn check_transition(
+    from: &Chunk,
+    to: &Chunk,
+    platform_kinds: &PlatformKindsResource,
+) -> Option<String> {
+    let from_surfaces = chunk_surfaces(from, platform_kinds).ok()?;
+    let to_surfaces = chunk_surfaces(to, platform_kinds).ok()?;
+
+    let mut cheats = chunk_cheats(from);
+    cheats.extend(to.required_cheats.iter());
+    let reach = PlayerReach::new(&cheats);
+
+    let exit = walk_chunk(&from_surfaces, &reach).1?;
+    let entry = to_surfaces.iter().find(|surface| surface.right >= 0.0)?;
+
+    // both origins follow the same furthest point, shifted by their own offset
+    let offset = from.next_chunk_offset + to.chunk_offset - from.chunk_offset;
+    check_jump(
+        offset + entry.left - exit.right,
+        entry.top - exit.ground,
+        &reach,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use ron::de::from_bytes;
+
+    use super::*;
+    use crate::platforms::chunk::{ChunksResource, PlatformData};
+    use crate::platforms::platform::{PlatformKind, PlatformKindData};
+
+    fn surface(left: f32, right: f32, top: f32) -> Surface {
+        Surface {
+            left,
+            right,
+            top,
+            bottom: top - 20.0,
+        }
+    }
+
+    #[test]
+    fn walkable_gap_needs_no_cheat() {
+        let reach = PlayerReach::new(&[]);
+        assert!(check_jump(WALKABLE_GAP, 0.0, &reach).is_none());
+        assert!(check_jump(WALKABLE_GAP + 1.0, 0.0, &reach).is_some());
+        assert!(check_jump(0.0, STEP_TOLERANCE + 1.0, &reach).is_some());
+    }
+
+    #[test]
+    fn cheats_widen_the_reach() {
+        let jump = PlayerReach::new(&[CheatCodeKind::Jump]);
+        let double_jump = PlayerReach::new(&[CheatCodeKind::Jump, CheatCodeKind::DoubleJump]);
+        let dash = PlayerReach::new(&[CheatCodeKind::Jump, CheatCodeKind::Dash]);
+
+        assert!(jump.max_gap() > WALKABLE_GAP);
+        assert!(double_jump.jump_height > jump.jump_height);
+        assert!(dash.max_gap() > jump.max_gap());
+        assert!(check_jump(jump.max_gap(), jump.max_step(), &jump).is_none());
+        assert!(check_jump(jump.max_gap() + 1.0, 0.0, &jump).is_some());
+    }
+
+    #[test]
+    fn walk_reaches_the_last_platform() {
+        let surfaces = [
+            surface(-100.0, 100.0, 0.0),
+            surface(100.0 + WALKABLE_GAP, 300.0, 0.0),
+        ];
+        let (errors, exit) = walk_chunk(&surfaces, &PlayerReach::new(&[]));
+
+        assert!(errors.is_empty());
+        let exit = exit.unwrap();
+        assert_eq!(exit.right, 300.0);
+        assert_eq!(exit.ground, 0.0);
+    }
+
+    #[test]
+    fn walk_reports_an_unreachable_gap() {
+        let reach = PlayerReach::new(&[CheatCodeKind::Jump]);
+        let surfaces = [
+            surface(-100.0, 100.0, 0.0),
+            surface(100.0 + reach.max_gap() + 10.0, 400.0, 0.0),
+        ];
+        let (errors, exit) = walk_chunk(&surfaces, &reach);
+
+        assert_eq!(errors.len(), 1);
+        // the walk goes on to report the next obstacles
+        assert_eq!(exit.unwrap().right, 400.0);
+    }
+
+    #[test]
+    fn walk_skips_platforms_above_the_head() {
+        let surfaces = [
+            surface(-100.0, 100.0, 0.0),
+            surface(500.0, 600.0, PLAYER_HEIGHT + 100.0),
+        ];
+        let (errors, exit) = walk_chunk(&surfaces, &PlayerReach::new(&[]));
+
+        assert!(errors.is_empty());
+        assert_eq!(exit.unwrap().right, 100.0);
+    }
+
+    #[test]
+    fn walk_needs_a_platform_after_the_origin() {
+        let (errors, exit) = walk_chunk(&[surface(-200.0, -100.0, 0.0)], &PlayerReach::new(&[]));
+
+        assert_eq!(errors.len(), 1);
+        assert!(exit.is_none());
+    }
+
+    fn ground_chunk(chunk_offset: f32, next_chunk_offset: f32) -> Chunk {
+        Chunk {
+            platforms: vec![PlatformData {
+                platform_kind: PlatformKind("Ground".to_string()),
+                position: Vec2::ZERO,
+                behavior: PlatformBehavior::Static,
+            }],
+            enemies: Vec::new(),
+            hazards: Vec::new(),
+            terminals: Vec::new(),
+            chars: Vec::new(),
+            required_cheats: Vec::new(),
+            rewarding_cheats: Vec::new(),
+            biomes: Vec::new(),
+            next_chunk_offset,
+            chunk_offset,
+            difficulty: 1,
+        }
+    }
+
+    #[test]
+    fn transition_counts_both_chunk_offsets() {
+        let mut kinds = HashMap::new();
+        kinds.insert(
+            PlatformKind("Ground".to_string()),
+            PlatformKindData {
+                half_extents: Vec2::new(100.0, 20.0),
+                sprite: String::new(),
+                sprite_offset: Vec2::ZERO,
+                friction: 0.0,
+                tags: Vec::new(),
+            },
+        );
+        let platform_kinds = PlatformKindsResource { kinds };
+
+        // the next ground starts where the previous one ends
+        let from = ground_chunk(80.0, 200.0);
+        assert!(check_transition(&from, &ground_chunk(80.0, 200.0), &platform_kinds).is_none());
+        // a wider offset opens a gap
+        let far = ground_chunk(80.0 + WALKABLE_GAP + 10.0, 200.0);
+        assert!(check_transition(&from, &far, &platform_kinds).is_some());
+    }
+
+    #[test]
+    fn shipped_chunks_can_be_cleared() {
+        let chunks: ChunksResource =
+            from_bytes(include_bytes!("../../assets/data/chunks.ron")).unwrap();
+        let platform_kinds: PlatformKindsResource =
+            from_bytes(include_bytes!("../../assets/data/platforms.ron")).unwrap();
+        let cheat_codes = CheatCodeResource::new();
+
+        for (i, chunk) in chunks
+            .prelude_chunks
+            .iter()
+            .chain(chunks.chunks.iter())
+            .enumerate()
+        {
+            let errors = check_chunk(chunk, &platform_kinds, &cheat_codes);
+            assert!(errors.is_empty(), "chunk {}: {:?}", i, errors);
+        }
+    }
+}
@@ -1,7 +1,10 @@
+use crate::biomes::{BiomeData, BiomesResource};
 use crate::runner::player::Player;
+use crate::runner::Checkpoint;
 use crate::states::GameStates;
 use bevy::prelude::*;
 use bevy_rapier2d::prelude::*;
+use rand::prelude::SliceRandom;
 
 const BACKGROUND_SPRITE_WIDTH: f32 = 816.;
 const BACKGROUND_SPRITE_HEIGHT: f32 = 480.;
@@ -9,11 +12,14 @@ const BACKGROUND_SPRITE_HEIGHT: f32 = 480.;
 const PLAYER_SPRITE_WIDTH: f32 = 168.;
 // TODO: remove magic number
 const CAMERA_OFFSET_X: f32 = 1060.;
+// left edge of the first background layer
+const FIRST_LAYER_X: f32 = -BACKGROUND_SPRITE_WIDTH * 1.5;
 
 #[derive(Debug, Component)]
 pub struct BackgroundLayer;
 
 pub struct BackgroundResource {
+    // left edge of the next background layer
     pub next_x: f32,
 }
 
@@ -22,7 +28,7 @@ pub struct BackgroundLayerPlugin;
 impl Plugin for BackgroundLayerPlugin {
     fn build(&self, app: &mut App) {
         app.insert_resource(BackgroundResource {
-            next_x: BACKGROUND_SPRITE_WIDTH * 2.0,
+            next_x: FIRST_LAYER_X,
         })
         .add_system_set(SystemSet::on_enter(GameStates::Main).with_system(spawn_initial_layers))
         .add_system_set(
@@ -37,51 +43,19 @@ fn spawn_initial_layers(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
     mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+    mut background_resource: ResMut<BackgroundResource>,
+    biomes: Res<BiomesResource>,
 ) {
-    let texture_handle = asset_server.load("cyberpunk-city.png");
-    let texture_atlas = TextureAtlas::from_grid(
-        texture_handle,
-        Vec2::new(BACKGROUND_SPRITE_WIDTH, BACKGROUND_SPRITE_HEIGHT),
-        1,
-        1,
-    );
-    let texture_atlas_handle = texture_atlases.add(texture_atlas);
-
-    commands
-        .spawn_bundle(SpriteSheetBundle {
-            texture_atlas: texture_atlas_handle.clone(),
-            transform: Transform {
-                scale: Vec3::new(1.0, 1.5, 1.0),
-                translation: Vec3::new(0.0, 0.0, 0.0),
-                ..Default::default()
-            },
-            ..Default::default()
-        })
-        .insert(BackgroundLayer {});
-
-    commands
-        .spawn_bundle(SpriteSheetBundle {
-            texture_atlas: texture_atlas_handle.clone(),
-            transform: Transform {
-                scale: Vec3::new(1.0, 1.5, 1.0),
-                translation: Vec3::new(-BACKGROUND_SPRITE_WIDTH, 0.0, 0.0),
-                ..Default::default()
-            },
-            ..Default::default()
-        })
-        .insert(BackgroundLayer);
-
-    commands
-        .spawn_bundle(SpriteSheetBundle {
-            texture_atlas: texture_atlas_handle.clone(),
-            transform: Transform {
-                scale: Vec3::new(1.0, 1.5, 1.0),
-                translation: Vec3::new(BACKGROUND_SPRITE_WIDTH, 0.0, 0.0),
-                ..Default::default()
-            },
-            ..Default::default()
-        })
-        .insert(BackgroundLayer);
+    background_resource.next_x = FIRST_LAYER_X;
+    while background_resource.next_x < -FIRST_LAYER_X {
+        background_resource.next_x += spawn_layer(
+            &mut commands,
+            &asset_server,
+            &mut texture_atlases,
+            biomes.at(0.0),
+            background_resource.next_x,
+        );
+    }
 }
 
 fn update_layers(
@@ -90,47 +64,67 @@ fn update_layers(
     mut texture_atlases: ResMut<Assets<TextureAtlas>>,
     camera: Query<&Transform, With<Player>>,
     mut background_resource: ResMut<BackgroundResource>,
+    biomes: Res<BiomesResource>,
 ) {
     for transform in camera.iter() {
         if transform.translation.x
-            >= (background_resource.next_x - BACKGROUND_SPRITE_WIDTH) - CAMERA_OFFSET_X
+            >= background_resource.next_x - BACKGROUND_SPRITE_WIDTH / 2. - CAMERA_OFFSET_X
         {
-            spawn_layer(
+            let biome = biomes.at(background_resource.next_x / 100.);
+            background_resource.next_x += spawn_layer(
                 &mut commands,
                 &asset_server,
                 &mut texture_atlases,
+                biome,
                 background_resource.next_x,
             );
-            background_resource.next_x += BACKGROUND_SPRITE_WIDTH;
         }
     }
 }
 
+/// Spawns a background of the biome starting at `left_x`, returns its width
 pub fn spawn_layer(
     commands: &mut Commands,
     asset_server: &AssetServer,
     texture_atlases: &mut Assets<TextureAtlas>,
-    x_pos: f32,
-) {
-    let texture_handle = asset_server.load("cyberpunk-city.png");
-    let texture_atlas = TextureAtlas::from_grid(
-        texture_handle,
-        Vec2::new(BACKGROUND_SPRITE_WIDTH, BACKGROUND_SPRITE_HEIGHT),
-        1,
-        1,
-    );
+    biome: Option<&BiomeData>,
+    left_x: f32,
+) -> f32 {
+    let background = biome.and_then(|biome| biome.backgrounds.choose(&mut rand::thread_rng()));
+    let (sprite, size, scale) = match background {
+        Some(background) => (
+            background.sprite.as_str(),
+            background.size,
+            background.scale,
+        ),
+        None => (
+            "cyberpunk-city.png",
+            Vec2::new(BACKGROUND_SPRITE_WIDTH, BACKGROUND_SPRITE_HEIGHT),
+            Vec2::new(1.0, 1.5),
+        ),
+    };
+    let width = size.x * scale.x;
+
+    let texture_handle = asset_server.load(sprite);
+    let texture_atlas = TextureAtlas::from_grid(texture_handle, size, 1, 1);
     let texture_atlas_handle = texture_atlases.add(texture_atlas);
     commands
         .spawn_bundle(SpriteSheetBundle {
-            texture_atlas: texture_atlas_handle.clone(),
+            sprite: TextureAtlasSprite {
+                color: biome.map_or(Color::WHITE, |biome| biome.tint_color()),
+                ..Default::default()
+            },
+            texture_atlas: texture_atlas_handle,
             transform: Transform {
-                scale: Vec3::new(1.0, 1.5, 1.0),
-                translation: Vec3::new(x_pos, 0.0, 0.0),
+                scale: scale.extend(1.0),
+                translation: Vec3::new(left_x + width / 2., 0.0, 0.0),
                 ..Default::default()
             },
             ..Default::default()
         })
         .insert(BackgroundLayer);
+
+    width
 }
 
 pub fn despawn_backgrounds(
@@ -138,12 +132,14 @@ pub fn despawn_backgrounds(
     rapier_config: Res<RapierConfiguration>,
     player_query: Query<&RigidBodyPositionComponent, With<Player>>,
     background_query: Query<(Entity, &Transform), With<BackgroundLayer>>,
+    checkpoint: Res<Checkpoint>,
 ) {
     for player_rb_pos in player_query.iter() {
         for (entity, background_transform) in background_query.iter() {
             if (player_rb_pos.position.translation.x * rapier_config.scale)
                 - background_transform.translation.x
                 > 10000.0
+                && !checkpoint.keeps(background_transform.translation.x + BACKGROUND_SPRITE_WIDTH)
             {
                 info!("despawning background");
                 commands.entity(entity).despawn_recursive();
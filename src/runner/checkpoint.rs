@@ -0,0 +1,28 @@
+use bevy::prelude::*;
+
+use super::player::SPAWN_POSITION;
+
+/// Where the player respawns after falling, set by the last terminal used
+pub struct Checkpoint {
+    // in pixels, none before the first terminal
+    pub position: Option<Vec2>,
+    // last static ground the player stood on, in pixels, used without terminal
+    pub safe_ground: Vec2,
+}
+
+impl Checkpoint {
+    /// Where the player respawns, in pixels
+    pub fn respawn_position(&self) -> Vec2 {
+        self.position.unwrap_or(self.safe_ground)
+    }
+
+    /// Whether something right of `x` has to be kept for the respawn
+    pub fn keeps(&self, x: f32) -> bool {
+        x >= self.respawn_position().x
+    }
+}
+
+pub fn reset_checkpoint(mut checkpoint: ResMut<Checkpoint>) {
+    checkpoint.position = None;
+    checkpoint.safe_ground = Vec2::from(SPAWN_POSITION);
+}
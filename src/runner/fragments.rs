@@ -0,0 +1,78 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+
+use crate::cheat_codes::CheatCodeKind;
+
+/// Random generator of the run, the code letters are scrambled with it
+pub struct RunSeed {
+    pub seed: u64,
+    rng: StdRng,
+}
+
+impl RunSeed {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    /// Order in which the letters of a code are laid out, never the code itself
+    pub fn permutation(&mut self, len: usize) -> Vec<usize> {
+        let identity = (0..len).collect::<Vec<usize>>();
+        let mut order = identity.clone();
+        while order == identity && len > 1 {
+            order.shuffle(&mut self.rng);
+        }
+        order
+    }
+}
+
+/// Letter of a cheat code spawned in a chunk
+#[derive(Debug, Component)]
+pub struct CodeFragment {
+    pub kind: CheatCodeKind,
+    // position of the letter in the code
+    pub index: usize,
+}
+
+/// Letters of each code the player has picked up during the run
+#[derive(Default)]
+pub struct CodeFragments {
+    revealed: HashMap<CheatCodeKind, Vec<usize>>,
+}
+
+impl CodeFragments {
+    pub fn reveal(&mut self, fragment: &CodeFragment) {
+        let revealed = self.revealed.entry(fragment.kind).or_default();
+        if !revealed.contains(&fragment.index) {
+            revealed.push(fragment.index);
+        }
+    }
+
+    /// Code with its hidden letters replaced, for example `_ a _ 7`
+    pub fn display(&self, kind: &CheatCodeKind, text: &str) -> String {
+        let revealed = self.revealed.get(kind);
+        text.chars()
+            .enumerate()
+            .map(|(i, ch)| match revealed {
+                Some(revealed) if revealed.contains(&i) => ch,
+                _ => '_',
+            })
+            .map(String::from)
+            .collect::<Vec<String>>()
+            .join(" ")
+    }
+}
+
+pub fn reset_run(mut run_seed: ResMut<RunSeed>, mut fragments: ResMut<CodeFragments>) {
+    let seed = rand::thread_rng().gen();
+    info!("[RunnerPlugin] Run seed: {}", seed);
+
+    *run_seed = RunSeed::new(seed);
+    *fragments = CodeFragments::default();
+}
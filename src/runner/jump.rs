@@ -0,0 +1,110 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::platforms::data::DataResource;
+
+/// Tuning of the jump controls, defined in `assets/data/jump.ron`
+#[derive(Deserialize)]
+pub struct JumpSettings {
+    // in seconds, the player can still jump after walking off a ledge
+    pub coyote_time: f32,
+    // in seconds, a press before landing jumps on landing
+    pub buffer_time: f32,
+    // part of the upward velocity kept when the jump is released early
+    pub release_cut: f32,
+}
+
+impl DataResource for JumpSettings {
+    const PATH: &'static str = "data/jump.ron";
+}
+
+/// Timing of the jump inputs of the player
+#[derive(Debug, Component)]
+pub struct JumpController {
+    // in seconds
+    since_grounded: f32,
+    since_pressed: f32,
+    is_pressed_now: bool,
+    // from a jump until the player falls
+    is_rising: bool,
+    // the jump was released early already
+    is_cut: bool,
+    // the last jump was done in the air
+    is_air_jump: bool,
+}
+
+impl Default for JumpController {
+    fn default() -> Self {
+        Self {
+            since_grounded: f32::INFINITY,
+            since_pressed: f32::INFINITY,
+            is_pressed_now: false,
+            is_rising: false,
+            is_cut: false,
+            is_air_jump: false,
+        }
+    }
+}
+
+impl JumpController {
+    pub fn update(&mut self, delta: f32, is_grounded: bool, is_pressed: bool, velocity_y: f32) {
+        if velocity_y <= 0.0 {
+            self.is_rising = false;
+        }
+        // the feet still touch the ground right after a jump, only a landing re-arms it
+        if is_grounded && !self.is_rising {
+            self.since_grounded = 0.0;
+            self.is_air_jump = false;
+        } else {
+            self.since_grounded += delta;
+        }
+        self.since_pressed = if is_pressed {
+            0.0
+        } else {
+            self.since_pressed + delta
+        };
+        self.is_pressed_now = is_pressed;
+    }
+
+    /// Whether a recent press meets the ground or the coyote window, consumes both
+    pub fn ground_jump(&mut self, settings: &JumpSettings) -> bool {
+        if self.since_pressed > settings.buffer_time || self.since_grounded > settings.coyote_time {
+            return false;
+        }
+        self.consume();
+        self.is_air_jump = false;
+        true
+    }
+
+    /// Whether the jump was pressed this frame, for the jumps in the air
+    pub fn air_jump(&mut self) -> bool {
+        if !self.is_pressed_now {
+            return false;
+        }
+        self.consume();
+        self.is_air_jump = true;
+        true
+    }
+
+    /// Whether the player is in the air after a jump in the air
+    pub fn is_air_jump(&self) -> bool {
+        self.is_air_jump
+    }
+
+    /// Vertical velocity after releasing the jump
+    pub fn release(&mut self, settings: &JumpSettings, velocity_y: f32) -> f32 {
+        if !self.is_rising || self.is_cut {
+            return velocity_y;
+        }
+        self.is_cut = true;
+        velocity_y * settings.release_cut
+    }
+
+    fn consume(&mut self) {
+        self.since_grounded = f32::INFINITY;
+        self.since_pressed = f32::INFINITY;
+        self.is_pressed_now = false;
+        self.is_rising = true;
+        self.is_cut = false;
+    }
+}
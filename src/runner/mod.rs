@@ -1,20 +1,42 @@
 use bevy::prelude::*;
 mod backgroundlayer;
+mod checkpoint;
+mod fragments;
+mod jump;
 mod lives_counter;
 mod player;
+mod state;
 
 use std::collections::HashMap;
 
 use crate::states::GameStates;
 
-pub use self::player::Player;
+pub use self::checkpoint::Checkpoint;
+pub use self::fragments::{CodeFragment, CodeFragments, RunSeed};
+pub use self::jump::{JumpController, JumpSettings};
+pub use self::player::{
+    damage_player, GameOverEvent, Player, DASH_IMPULSE, JUMP_IMPULSE, PLAYER_FEET_HEIGHT,
+    PLAYER_HEIGHT, PLAYER_SPEED, PLAYER_WIDTH, SPAWN_POSITION,
+};
+pub use self::state::{PlayerState, PlayerStateChangedEvent};
 
 pub struct RunnerPlugin;
 
 impl Plugin for RunnerPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugin(backgroundlayer::BackgroundLayerPlugin)
-            .add_plugin(player::PlayerPlugin);
+            .add_plugin(player::PlayerPlugin)
+            .insert_resource(Checkpoint {
+                position: None,
+                safe_ground: Vec2::from(SPAWN_POSITION),
+            })
+            .insert_resource(RunSeed::new(0))
+            .insert_resource(CodeFragments::default())
+            .add_system_set(
+                SystemSet::on_enter(GameStates::Main)
+                    .with_system(checkpoint::reset_checkpoint)
+                    .with_system(fragments::reset_run.before("generate_prelude_chunk")),
+            );
 
         app.add_system_set(
             SystemSet::on_enter(GameStates::Main)
@@ -44,4 +66,11 @@ impl CollectedChars {
             self.values_map.insert(c, 0);
         }
     }
+
+    pub fn push(&mut self, c: char) {
+        self.values.push(c);
+        if let Some(count) = self.values_map.get_mut(&c) {
+            *count += 1;
+        }
+    }
 }
@@ -1,19 +1,50 @@
 use std::time::Duration;
 
-use crate::enemies::Enemy;
-use crate::{camera::TwoDCameraComponent, effects, physics, platforms, states::GameStates};
+use crate::camera::{CameraLock, TwoDCameraComponent};
+use crate::controls::{InputAction, InputBindings};
+use crate::enemies::{Enemy, EnemyAi, EnemyState};
+use crate::{effects, physics, platforms, states::GameStates};
 use bevy::math::Vec3Swizzles;
 use bevy::{prelude::*, render::camera::Camera};
 use bevy_kira_audio::{Audio, AudioChannel};
 use bevy_rapier2d::prelude::*;
 use rand::Rng;
+use ron::de::from_bytes;
 use std::collections::HashMap;
 
-use super::CollectedChars;
+use super::jump::{JumpController, JumpSettings};
+use super::state::{self, PlayerState, PlayerStateChangedEvent};
+use super::{Checkpoint, CodeFragment, CodeFragments, CollectedChars};
 use crate::cheat_codes::{CheatCodeKind, CheatCodeResource};
 use crate::interactables::{CharTextComponent, InteractableComponent, InteractableType};
+use crate::platforms::data;
+use crate::stats::EnemyKilledEvent;
 use crate::toast::ShowToast;
 
+// collider sizes in pixels
+pub const PLAYER_WIDTH: f32 = 30.0;
+pub const PLAYER_HEIGHT: f32 = 70.0;
+pub const PLAYER_FEET_HEIGHT: f32 = 35.0;
+// max horizontal speed without speed boost, times the physics scale in physics units per second
+pub const PLAYER_SPEED: f32 = 8.0;
+pub const JUMP_IMPULSE: f32 = 1500.0;
+pub const DASH_IMPULSE: f32 = 1000.0;
+// time the player can't be hurt again after losing a life, in seconds
+pub const HURT_INVINCIBILITY: f32 = 1.0;
+pub const RESPAWN_INVINCIBILITY: f32 = 2.0;
+// where the run starts and the player respawns without checkpoint, in pixels
+pub const SPAWN_POSITION: [f32; 2] = [0.0, 300.0];
+// the player loses a life below this height, in pixels
+const FALL_HEIGHT: f32 = -400.0;
+// height of the respawn above the checkpoint, in pixels
+const RESPAWN_HEIGHT: f32 = 50.0;
+// vertical scale of the sprite, standing and crouching
+const SPRITE_SCALE: f32 = 1.5;
+const CROUCH_SPRITE_SCALE: f32 = 1.0;
+// in pixels per second
+const STOMP_BOUNCE_SPEED: f32 = 500.0;
+const KNOCKBACK_SPEED: (f32, f32) = (300.0, 300.0);
+
 #[derive(Debug, Component)]
 pub struct Player {
     pub speed: f32,
@@ -25,12 +56,18 @@ pub struct Player {
     pub dash_input_timer: Timer,
     pub dash_cooldown_timer: Timer,
     pub dash_input_count: u8,
+    // from the dash input until the end of its animation, read the state instead
     pub is_dashing: bool,
+    pub hurt_timer: Timer,
+    // set by `update_player_state`, the crouch and the dash are read from it
+    pub state: PlayerState,
 }
 
 #[derive(Debug)]
 pub struct FeetTouchingPlatforms {
     pub platforms: Vec<Entity>,
+    // one-way platforms the player goes through, not ground until landed on
+    pub passing: Vec<Entity>,
 }
 
 #[derive(Component)]
@@ -49,6 +86,11 @@ impl Plugin for PlayerPlugin {
         };
         collected_chars_list.initialize_map();
         app.insert_resource(collected_chars_list)
+            .insert_resource(
+                from_bytes::<JumpSettings>(include_bytes!("../../assets/data/jump.ron")).unwrap(),
+            )
+            .add_startup_system(data::load_data::<JumpSettings>)
+            .add_system(data::reload_data::<JumpSettings>)
             .insert_resource(PlayerAnimationResource {
                 run_right: AnimationData {
                     length: 8,
@@ -83,15 +125,33 @@ impl Plugin for PlayerPlugin {
                     .label("player_feet"),
             )
             .add_system_set(SystemSet::on_exit(GameStates::Main).with_system(despawn_character))
+            .add_event::<PlayerStateChangedEvent>()
+            .add_system_set(
+                SystemSet::on_update(GameStates::Main)
+                    .with_system(
+                        state::update_player_state
+                            .label("player_state")
+                            .after("move_character"),
+                    )
+                    .with_system(
+                        state::change_player_state
+                            .label("player_state_hooks")
+                            .after("player_state"),
+                    )
+                    .with_system(animate_sprite.after("player_state_hooks"))
+                    .with_system(crouch_sprite.after("player_state")),
+            )
             .add_system_set(
                 SystemSet::on_update(GameStates::Main)
                     .with_system(follow_player_camera)
-                    .with_system(animate_sprite)
-                    .with_system(move_character)
+                    .with_system(move_character.label("move_character"))
+                    .with_system(tick_hurt_timer)
                     .after("player_feet")
                     .with_system(detect_char_interactable)
-                    .with_system(player_collide_enemy)
-                    .with_system(player_fall_damage)
+                    // the damages read the state of this frame
+                    .with_system(player_collide_enemy.after("player_state"))
+                    .with_system(player_fall_damage.after("player_state"))
+                    .with_system(update_safe_ground)
                     .with_system(detect_cheat_code_activation)
                     .with_system(show_terminal_toaster_notification),
             );
@@ -118,33 +178,43 @@ fn spawn_character(
     let texture_handle = asset_server.load("player.png");
     let texture_atlas = TextureAtlas::from_grid(texture_handle, Vec2::new(71.0, 67.0), 8, 5);
     let texture_atlas_handle = texture_atlases.add(texture_atlas);
-    let player = Player {
-        speed: 8.0,
+    let mut player = Player {
+        speed: PLAYER_SPEED,
         lives: 6,
         acceleration: 0.12,
         deceleration: 0.1,
-        feet_touching_platforms: FeetTouchingPlatforms { platforms: vec![] },
+        feet_touching_platforms: FeetTouchingPlatforms {
+            platforms: vec![],
+            passing: vec![],
+        },
         jump_count: 0,
         dash_input_timer: Timer::from_seconds(0.25, false),
         dash_cooldown_timer: Timer::from_seconds(1.5, false),
         dash_input_count: 1,
         is_dashing: false,
+        hurt_timer: Timer::from_seconds(HURT_INVINCIBILITY, false),
+        state: PlayerState::Idle,
     };
+    // not invincible at the start of the run
+    player
+        .hurt_timer
+        .tick(Duration::from_secs_f32(HURT_INVINCIBILITY));
 
-    let collider_size_hx = 30.0 / rapier_config.scale / 2.0;
-    let collider_size_hy = 70.0 / rapier_config.scale / 2.0;
+    let collider_size_hx = PLAYER_WIDTH / rapier_config.scale / 2.0;
+    let collider_size_hy = PLAYER_HEIGHT / rapier_config.scale / 2.0;
 
     commands
         .spawn_bundle(RigidBodyBundle {
             body_type: RigidBodyType::Dynamic.into(),
             mass_properties: RigidBodyMassPropsFlags::ROTATION_LOCKED.into(),
-            position: Vec2::new(0.0, 300.0 / rapier_config.scale).into(),
+            position: (Vec2::from(SPAWN_POSITION) / rapier_config.scale).into(),
             ..Default::default()
         })
         .insert_bundle(ColliderBundle {
             shape: ColliderShape::cuboid(collider_size_hx, collider_size_hy).into(),
             flags: ColliderFlags {
                 active_events: ActiveEvents::CONTACT_EVENTS,
+                solver_groups: InteractionGroups::new(physics::PLAYER_GROUP, u32::MAX),
                 ..Default::default()
             }
             .into(),
@@ -159,12 +229,13 @@ fn spawn_character(
         .insert(ColliderPositionSync::Discrete)
         .insert(Name::new("Player"))
         .insert(player)
+        .insert(JumpController::default())
         .with_children(|parent| {
             parent
                 .spawn_bundle(SpriteSheetBundle {
                     texture_atlas: texture_atlas_handle,
                     transform: Transform {
-                        scale: Vec3::new(1.5, 1.5, 1.0),
+                        scale: Vec3::new(1.5, SPRITE_SCALE, 1.0),
                         translation: Vec3::new(0.0, 12.0, 100.0),
                         ..Default::default()
                     },
@@ -176,10 +247,10 @@ fn spawn_character(
                 .spawn_bundle(ColliderBundle {
                     shape: ColliderShape::cuboid(
                         collider_size_hx,
-                        35.0 / rapier_config.scale / 2.0,
+                        PLAYER_FEET_HEIGHT / rapier_config.scale / 2.0,
                     )
                     .into(),
-                    position: [0.0, -35.0 / rapier_config.scale / 2.0].into(),
+                    position: [0.0, -PLAYER_FEET_HEIGHT / rapier_config.scale / 2.0].into(),
                     collider_type: ColliderType::Sensor.into(),
                     flags: ColliderFlags {
                         active_events: ActiveEvents::INTERSECTION_EVENTS,
@@ -235,6 +306,10 @@ pub fn player_feet(
                                 .push(collider1_entity);
                         }
                     } else if collider1_entity == feet_entity {
+                        player
+                            .feet_touching_platforms
+                            .passing
+                            .retain(|x| *x != collider2_entity);
                         while player
                             .feet_touching_platforms
                             .platforms
@@ -249,6 +324,10 @@ pub fn player_feet(
                             player.feet_touching_platforms.platforms.remove(index);
                         }
                     } else if collider2_entity == feet_entity {
+                        player
+                            .feet_touching_platforms
+                            .passing
+                            .retain(|x| *x != collider1_entity);
                         while player
                             .feet_touching_platforms
                             .platforms
@@ -283,6 +362,21 @@ pub struct AnimationData {
     pub offset: usize,
 }
 
+impl PlayerAnimationResource {
+    /// Animation of a state, `velocity_x` choosing the side of the run
+    pub fn animation(&self, state: PlayerState, velocity_x: f32) -> &AnimationData {
+        match state {
+            PlayerState::Run if velocity_x < 0.0 => &self.run_left,
+            PlayerState::Run => &self.run_right,
+            PlayerState::Jump | PlayerState::DoubleJump | PlayerState::Fall => &self.jump,
+            PlayerState::Dash => &self.dash_attack,
+            PlayerState::Idle | PlayerState::Crouch | PlayerState::Hurt | PlayerState::Dead => {
+                &self.idle
+            }
+        }
+    }
+}
+
 pub fn animate_sprite(
     time: Res<Time>,
     mut player_animation_resource: ResMut<PlayerAnimationResource>,
@@ -295,147 +389,62 @@ pub fn animate_sprite(
     for (mut player, rb_vel) in player_query.iter_mut() {
         for (mut timer, mut sprite) in query.iter_mut() {
             timer.0.tick(time.delta());
-            if timer.0.just_finished() {
-                if player.is_dashing {
-                    if sprite.index
-                        == player_animation_resource.dash_attack.offset
-                            + player_animation_resource.dash_attack.length
-                            - 1
-                    {
-                        player.is_dashing = false;
-                    }
-                    if sprite.index < player_animation_resource.dash_attack.offset
-                        || sprite.index
-                            >= (player_animation_resource.dash_attack.length
-                                + player_animation_resource.dash_attack.offset)
-                    {
-                        sprite.index = player_animation_resource.dash_attack.offset;
-                        let audio_channel = AudioChannel::new("ability-channel".to_owned());
-                        audio.set_volume_in_channel(5.0, &audio_channel);
-                        audio.play_in_channel(asset_server.load("dash.ogg"), &audio_channel);
-                    } else if sprite.index
-                        < (player_animation_resource.dash_attack.length
-                            + player_animation_resource.dash_attack.offset)
-                            - 1
-                    {
-                        sprite.index += 1;
-                    }
-                } else if player.feet_touching_platforms.platforms.is_empty() {
-                    // player is jumping
-                    if sprite.index < player_animation_resource.jump.offset
-                        || sprite.index
-                            >= (player_animation_resource.jump.length
-                                + player_animation_resource.jump.offset)
-                    {
-                        sprite.index = player_animation_resource.jump.offset;
-                        let audio_channel = AudioChannel::new("movement-channel".to_owned());
-                        audio.set_volume_in_channel(10.0, &audio_channel);
-                        audio.play_in_channel(asset_server.load("jump.ogg"), &audio_channel);
-                    } else if sprite.index
-                        < (player_animation_resource.jump.length
-                            + player_animation_resource.jump.offset)
-                            - 1
-                    {
-                        sprite.index += 1;
-                    }
-                } else {
-                    if rb_vel.linvel.x > 0.0 {
-                        // player is running right
-                        if sprite.index >= player_animation_resource.run_right.length {
-                            sprite.index = 0;
-                            player_animation_resource.run_step_counter = 0;
-                        } else {
-                            sprite.index =
-                                (sprite.index + 1) % player_animation_resource.run_right.length;
-                            player_animation_resource.run_step_counter += 1;
-                        }
-                        let audio_channel = AudioChannel::new("movement-channel".to_owned());
-                        if player_animation_resource.run_step_counter % 3 == 0 {
-                            audio.set_volume_in_channel(
-                                15.0 * (rb_vel.linvel.x.abs()
-                                    / (player.speed * rapier_config.scale)),
-                                &audio_channel,
-                            );
-                            audio.play_in_channel(
-                                asset_server.load(
-                                    format!(
-                                        "footsteps/{}.ogg",
-                                        rand::thread_rng().gen_range(0..10)
-                                    )
-                                    .as_str(),
-                                ),
-                                &audio_channel,
-                            );
-                        }
-                    } else if rb_vel.linvel.x < 0.0 {
-                        //player is running left
-                        if sprite.index < player_animation_resource.run_left.offset
-                            || sprite.index
-                                >= (player_animation_resource.run_left.length
-                                    + player_animation_resource.run_left.offset)
-                        {
-                            sprite.index = player_animation_resource.run_left.offset;
-                            player_animation_resource.run_step_counter = 0;
-                        } else {
-                            sprite.index = ((sprite.index + 1)
-                                % player_animation_resource.run_left.length)
-                                + player_animation_resource.run_left.offset;
-                            player_animation_resource.run_step_counter += 1;
-                        }
-                        let audio_channel = AudioChannel::new("movement-channel".to_owned());
-                        if player_animation_resource.run_step_counter % 3 == 0 {
-                            audio.set_volume_in_channel(
-                                15.0 * (rb_vel.linvel.x.abs()
-                                    / (player.speed * rapier_config.scale)),
-                                &audio_channel,
-                            );
-                            audio.play_in_channel(
-                                asset_server.load(
-                                    format!(
-                                        "footsteps/{}.ogg",
-                                        rand::thread_rng().gen_range(0..10)
-                                    )
-                                    .as_str(),
-                                ),
-                                &audio_channel,
-                            );
-                        }
-                    } else {
-                        //player is idling
-                        if sprite.index < player_animation_resource.idle.offset
-                            || sprite.index
-                                >= (player_animation_resource.idle.length
-                                    + player_animation_resource.idle.offset)
-                        {
-                            sprite.index = player_animation_resource.idle.offset;
-                        } else {
-                            sprite.index = ((sprite.index + 1)
-                                % player_animation_resource.idle.length)
-                                + player_animation_resource.idle.offset;
-                        }
-                    }
+            if !timer.0.just_finished() {
+                continue;
+            }
+
+            let animation = player_animation_resource.animation(player.state, rb_vel.linvel.x);
+            let first = animation.offset;
+            let last = animation.offset + animation.length - 1;
+
+            // the dash lasts as long as its animation
+            if player.state == PlayerState::Dash && sprite.index == last {
+                player.is_dashing = false;
+            }
+
+            if sprite.index < first || sprite.index > last {
+                sprite.index = first;
+            } else if sprite.index < last {
+                sprite.index += 1;
+            } else if player.state.is_looping() {
+                sprite.index = first;
+            }
+
+            if player.state == PlayerState::Run {
+                if player_animation_resource.run_step_counter % 3 == 0 {
+                    let audio_channel = AudioChannel::new("movement-channel".to_owned());
+                    audio.set_volume_in_channel(
+                        15.0 * (rb_vel.linvel.x.abs() / (player.speed * rapier_config.scale)),
+                        &audio_channel,
+                    );
+                    audio.play_in_channel(
+                        asset_server.load(
+                            format!("footsteps/{}.ogg", rand::thread_rng().gen_range(0..10))
+                                .as_str(),
+                        ),
+                        &audio_channel,
+                    );
                 }
+                player_animation_resource.run_step_counter += 1;
             }
         }
     }
 }
 
 fn move_character(
-    keyboard_input: Res<Input<KeyCode>>,
+    actions: Res<Input<InputAction>>,
     rapier_config: Res<RapierConfiguration>,
+    jump_settings: Res<JumpSettings>,
     mut query: Query<(
         &mut Player,
+        &mut JumpController,
         &mut RigidBodyVelocityComponent,
         &RigidBodyMassPropsComponent,
     )>,
-    mut animation_query: Query<&mut TextureAtlasSprite, With<PlayerAnimationTimer>>,
-    player_animation_resource: Res<PlayerAnimationResource>,
     cheat_codes: ResMut<CheatCodeResource>,
-    asset_server: Res<AssetServer>,
-    audio: Res<Audio>,
     time: Res<Time>,
 ) {
-    for (mut player, mut rb_vel, rb_mprops) in query.iter_mut() {
+    for (mut player, mut jump_controller, mut rb_vel, rb_mprops) in query.iter_mut() {
         // update acceleration value
         if cheat_codes.is_code_activated(&CheatCodeKind::SpeedBoost3) {
             player.acceleration = 0.15;
@@ -451,28 +460,40 @@ fn move_character(
             player.speed = 8.3;
         }
 
-        let _up = keyboard_input.pressed(KeyCode::W);
-        let _down = keyboard_input.pressed(KeyCode::S);
-        let right = keyboard_input.pressed(KeyCode::D);
-        let dash = keyboard_input.just_released(KeyCode::D);
-
-        let jump = cheat_codes.is_code_activated(&CheatCodeKind::Jump)
-            && keyboard_input.just_released(KeyCode::Space)
-            && !player.feet_touching_platforms.platforms.is_empty()
-            || (cheat_codes.is_code_activated(&CheatCodeKind::DoubleJump)
-                && keyboard_input.just_released(KeyCode::Space));
+        let right = actions.pressed(InputAction::MoveRight);
+        // double tap of move right, or the dash binding
+        let dash = actions.just_released(InputAction::MoveRight);
+        let dash_now = actions.just_pressed(InputAction::Dash);
+
+        jump_controller.update(
+            time.delta_seconds(),
+            !player.feet_touching_platforms.platforms.is_empty(),
+            actions.just_pressed(InputAction::Jump),
+            rb_vel.linvel.y,
+        );
+        // releasing the jump early makes it lower
+        if actions.just_released(InputAction::Jump) {
+            rb_vel.linvel.y = jump_controller.release(&jump_settings, rb_vel.linvel.y);
+        }
 
         let left = cheat_codes.is_code_activated(&CheatCodeKind::MoveLeft)
-            && keyboard_input.pressed(KeyCode::A);
+            && actions.pressed(InputAction::MoveLeft);
 
         let x_axis = -(left as i8) + right as i8;
 
-        if dash && cheat_codes.is_code_activated(&CheatCodeKind::Dash) {
+        if dash_now
+            && cheat_codes.is_code_activated(&CheatCodeKind::Dash)
+            && player.dash_cooldown_timer.finished()
+        {
+            rb_vel.apply_impulse(rb_mprops, Vec2::new(DASH_IMPULSE, 0.0).into());
+            player.is_dashing = true;
+            player.dash_cooldown_timer.reset()
+        } else if dash && cheat_codes.is_code_activated(&CheatCodeKind::Dash) {
             if player.dash_input_count == 0 {
                 player.dash_input_count = 1;
                 player.dash_input_timer.reset();
             } else if player.dash_input_count == 1 && player.dash_cooldown_timer.finished() {
-                rb_vel.apply_impulse(rb_mprops, Vec2::new(1000.0, 0.0).into());
+                rb_vel.apply_impulse(rb_mprops, Vec2::new(DASH_IMPULSE, 0.0).into());
                 player.is_dashing = true;
                 player.dash_cooldown_timer.reset()
             }
@@ -508,38 +529,37 @@ fn move_character(
             rb_vel.linvel.x += player.acceleration * 2.0;
         }
 
-        if jump {
-            if !player.feet_touching_platforms.platforms.is_empty() {
-                // single jump
-                physics::jump(1500.0, &mut rb_vel, rb_mprops);
-                if cheat_codes.is_code_activated(&CheatCodeKind::DoubleJump) {
-                    player.jump_count = 1;
-                } else {
-                    player.jump_count = 0;
-                }
-            } else if player.jump_count == 1 {
-                // double jump
-                rb_vel.linvel.y = 0.0;
-                physics::jump(1500.0, &mut rb_vel, rb_mprops);
-                for mut sprite in animation_query.iter_mut() {
-                    sprite.index = player_animation_resource.jump.offset;
-                }
-                let audio_channel = AudioChannel::new("movement-channel".to_owned());
-                audio.set_volume_in_channel(10.0, &audio_channel);
-                audio.play_in_channel(asset_server.load("jump.ogg"), &audio_channel);
+        if cheat_codes.is_code_activated(&CheatCodeKind::Jump)
+            && jump_controller.ground_jump(&jump_settings)
+        {
+            // single jump, also off a ledge or just before landing
+            rb_vel.linvel.y = 0.0;
+            physics::jump(JUMP_IMPULSE, &mut rb_vel, rb_mprops);
+            if cheat_codes.is_code_activated(&CheatCodeKind::DoubleJump) {
+                player.jump_count = 1;
+            } else {
                 player.jump_count = 0;
             }
+        } else if cheat_codes.is_code_activated(&CheatCodeKind::DoubleJump)
+            && player.jump_count == 1
+            && jump_controller.air_jump()
+        {
+            // double jump
+            rb_vel.linvel.y = 0.0;
+            physics::jump(JUMP_IMPULSE, &mut rb_vel, rb_mprops);
+            player.jump_count = 0;
         }
     }
 }
 
 fn follow_player_camera(
+    camera_lock: Res<CameraLock>,
     player: Query<&Transform, (With<Player>, Without<Camera>)>,
     mut camera: Query<&mut Transform, (With<TwoDCameraComponent>, Without<Player>)>,
 ) {
     if let Some(player) = player.iter().next() {
         for mut transform in camera.iter_mut() {
-            transform.translation.x = player.translation.x;
+            transform.translation.x = camera_lock.x.unwrap_or(player.translation.x);
         }
     }
 }
@@ -547,18 +567,21 @@ fn follow_player_camera(
 fn detect_char_interactable(
     mut commands: Commands,
     mut collected_chars: ResMut<CollectedChars>,
+    mut fragments: ResMut<CodeFragments>,
     player_query: Query<&Transform, With<Player>>,
     interactable_query: Query<(
         Entity,
         &InteractableComponent,
         &Transform,
         &CharTextComponent,
+        Option<&CodeFragment>,
     )>,
     asset_server: Res<AssetServer>,
     audio: Res<Audio>,
 ) {
     if let Some(player_transform) = player_query.iter().next() {
-        for (entity, interactable, transform, char_component) in interactable_query.iter() {
+        for (entity, interactable, transform, char_component, fragment) in interactable_query.iter()
+        {
             match interactable.interactable_type {
                 InteractableType::CharText => {
                     let distance_x = player_transform.translation.x - transform.translation.x;
@@ -582,8 +605,11 @@ fn detect_char_interactable(
                                 .get_mut(&char_component.value)
                                 .unwrap() += 1;
                         }
+                        if let Some(fragment) = fragment {
+                            fragments.reveal(fragment);
+                        }
 
-                        commands.entity(entity).despawn();
+                        commands.entity(entity).despawn_recursive();
                     }
                 }
                 _ => {}
@@ -594,27 +620,141 @@ fn detect_char_interactable(
 
 pub struct GameOverEvent;
 
+/// Remembers the last static ground under the player, the respawn point without terminal
+fn update_safe_ground(
+    mut checkpoint: ResMut<Checkpoint>,
+    player_query: Query<(&Player, &Transform)>,
+    unsafe_platform_query: Query<
+        (),
+        Or<(
+            With<platforms::platform::MovingPlatform>,
+            With<platforms::platform::CrumblingPlatform>,
+            With<platforms::platform::OneWayPlatform>,
+        )>,
+    >,
+) {
+    for (player, transform) in player_query.iter() {
+        let platforms = &player.feet_touching_platforms.platforms;
+        if !platforms.is_empty()
+            && platforms
+                .iter()
+                .all(|platform| unsafe_platform_query.get(*platform).is_err())
+        {
+            checkpoint.safe_ground = transform.translation.truncate();
+        }
+    }
+}
+
+/// Falling costs a life and brings the player back to the last checkpoint
 pub fn player_fall_damage(
-    mut player_query: Query<(&mut Player, &Transform)>,
+    mut player_query: Query<(
+        &mut Player,
+        &Transform,
+        &mut RigidBodyPositionComponent,
+        &mut RigidBodyVelocityComponent,
+    )>,
+    checkpoint: Res<Checkpoint>,
+    rapier_config: Res<RapierConfiguration>,
     mut game_over_event: EventWriter<GameOverEvent>,
     mut game_state: ResMut<State<GameStates>>,
 ) {
-    for (mut player, transform) in player_query.iter_mut() {
-        if transform.translation.y < -400.0 {
-            player.lives = 0;
-            game_over_event.send(GameOverEvent);
-            info!("Fell down hole");
-            game_state.push(GameStates::GameOver).unwrap();
+    for (mut player, transform, mut rb_pos, mut rb_vel) in player_query.iter_mut() {
+        if transform.translation.y >= FALL_HEIGHT {
+            continue;
+        }
+
+        info!("Fell down hole");
+        lose_life(&mut player, &mut game_over_event, &mut game_state);
+        if player.lives <= 0 {
+            continue;
+        }
+
+        let respawn =
+            (checkpoint.respawn_position() + Vec2::new(0.0, RESPAWN_HEIGHT)) / rapier_config.scale;
+        rb_pos.position.translation.x = respawn.x;
+        rb_pos.position.translation.y = respawn.y;
+        rb_pos.next_position = rb_pos.position;
+        rb_vel.linvel = Vec2::ZERO.into();
+
+        player.is_dashing = false;
+        player.feet_touching_platforms.platforms.clear();
+        player.feet_touching_platforms.passing.clear();
+        player.hurt_timer = Timer::from_seconds(RESPAWN_INVINCIBILITY, false);
+    }
+}
+
+/// Removes a life unless the player is dashing or was just hurt
+pub fn damage_player(
+    player: &mut Player,
+    game_over_event: &mut EventWriter<GameOverEvent>,
+    game_state: &mut State<GameStates>,
+) {
+    if player.state == PlayerState::Dash || !player.hurt_timer.finished() {
+        return;
+    }
+
+    lose_life(player, game_over_event, game_state);
+    player.hurt_timer = Timer::from_seconds(HURT_INVINCIBILITY, false);
+}
+
+fn lose_life(
+    player: &mut Player,
+    game_over_event: &mut EventWriter<GameOverEvent>,
+    game_state: &mut State<GameStates>,
+) {
+    // the game over is already on its way
+    if player.lives <= 0 {
+        return;
+    }
+
+    player.lives -= 1;
+    if player.lives <= 0 {
+        game_over_event.send(GameOverEvent);
+        game_state.push(GameStates::GameOver).unwrap();
+    }
+}
+
+fn tick_hurt_timer(time: Res<Time>, mut player_query: Query<&mut Player>) {
+    for mut player in player_query.iter_mut() {
+        player.hurt_timer.tick(time.delta());
+    }
+}
+
+fn crouch_sprite(
+    player_query: Query<&Player>,
+    mut sprite_query: Query<&mut Transform, With<PlayerAnimationTimer>>,
+) {
+    for player in player_query.iter() {
+        for mut transform in sprite_query.iter_mut() {
+            transform.scale.y = if player.state == PlayerState::Crouch {
+                CROUCH_SPRITE_SCALE
+            } else {
+                SPRITE_SCALE
+            };
         }
     }
 }
 
 pub fn player_collide_enemy(
     mut commands: Commands,
-    mut player_query: Query<(Entity, &mut Player)>,
-    enemy_query: Query<(Entity, &Transform), With<Enemy>>,
+    rapier_config: Res<RapierConfiguration>,
+    mut player_query: Query<(
+        Entity,
+        &mut Player,
+        &Transform,
+        &mut RigidBodyVelocityComponent,
+    )>,
+    mut enemy_query: Query<(
+        Entity,
+        &mut Enemy,
+        &mut EnemyAi,
+        &Transform,
+        &mut ColliderFlagsComponent,
+        Option<&Parent>,
+    )>,
     mut contact_events: EventReader<ContactEvent>,
     mut game_over_event: EventWriter<GameOverEvent>,
+    mut enemy_killed_event: EventWriter<EnemyKilledEvent>,
     mut texture_atlases: ResMut<Assets<TextureAtlas>>,
     asset_server: Res<AssetServer>,
     audio: Res<Audio>,
@@ -622,30 +762,72 @@ pub fn player_collide_enemy(
 ) {
     for contact_event in contact_events.iter() {
         if let ContactEvent::Started(h1, h2) = contact_event {
-            for (player_entity, mut player) in player_query.iter_mut() {
-                for (enemy_entity, enemy_transform) in enemy_query.iter() {
-                    if h1.entity() == player_entity && h2.entity() == enemy_entity
-                        || h2.entity() == player_entity && h1.entity() == enemy_entity
+            for (player_entity, mut player, player_transform, mut rb_vel) in player_query.iter_mut()
+            {
+                for (enemy_entity, mut enemy, mut ai, enemy_transform, mut flags, chunk) in
+                    enemy_query.iter_mut()
+                {
+                    if !(h1.entity() == player_entity && h2.entity() == enemy_entity
+                        || h2.entity() == player_entity && h1.entity() == enemy_entity)
                     {
-                        if !player.is_dashing {
-                            player.lives -= 1;
-                        }
-                        commands.entity(enemy_entity).despawn();
-                        // spawn explostion
-                        effects::spawn_explosion(
-                            enemy_transform.translation.xy(),
-                            &mut commands,
-                            &asset_server,
-                            &mut texture_atlases,
-                        );
-                        let audio_channel = AudioChannel::new("explosion-channel".to_owned());
-                        audio.set_volume_in_channel(0.6, &audio_channel);
-                        audio.play_in_channel(asset_server.load("explosion.ogg"), &audio_channel);
-                        if player.lives <= 0 {
-                            game_over_event.send(GameOverEvent);
-                            game_state.push(GameStates::GameOver).unwrap();
-                        }
+                        continue;
                     }
+
+                    // the enemy turns dead on the next update of its AI
+                    if enemy.health == 0
+                        || matches!(ai.state, EnemyState::Stunned | EnemyState::Dead)
+                    {
+                        continue;
+                    }
+
+                    // the feet of the player above the middle of the enemy, on the way down
+                    let is_stomp = player_transform.translation.y - PLAYER_HEIGHT / 2.0
+                        >= enemy_transform.translation.y
+                        && rb_vel.linvel.y <= 0.0;
+
+                    if !is_stomp && player.state != PlayerState::Dash {
+                        damage_player(&mut player, &mut game_over_event, &mut game_state);
+
+                        let direction = (player_transform.translation.x
+                            - enemy_transform.translation.x)
+                            .signum();
+                        rb_vel.linvel =
+                            (Vec2::new(direction * KNOCKBACK_SPEED.0, KNOCKBACK_SPEED.1)
+                                / rapier_config.scale)
+                                .into();
+                        continue;
+                    }
+
+                    if is_stomp {
+                        rb_vel.linvel.y = STOMP_BOUNCE_SPEED / rapier_config.scale;
+                    }
+
+                    // tougher enemies take several hits
+                    enemy.health = enemy.health.saturating_sub(1);
+                    if enemy.health > 0 {
+                        ai.stun();
+                        continue;
+                    }
+
+                    enemy_killed_event.send(EnemyKilledEvent {
+                        kind: enemy.kind.clone(),
+                        position: enemy_transform.translation.xy(),
+                        score: enemy.score,
+                        chunk: chunk.map(|parent| parent.0),
+                    });
+                    // the AI plays the death, the player goes through the enemy meanwhile
+                    flags.solver_groups =
+                        InteractionGroups::new(u32::MAX, u32::MAX ^ physics::PLAYER_GROUP);
+                    // spawn explostion
+                    effects::spawn_explosion(
+                        enemy_transform.translation.xy(),
+                        &mut commands,
+                        &asset_server,
+                        &mut texture_atlases,
+                    );
+                    let audio_channel = AudioChannel::new("explosion-channel".to_owned());
+                    audio.set_volume_in_channel(0.6, &audio_channel);
+                    audio.play_in_channel(asset_server.load("explosion.ogg"), &audio_channel);
                 }
             }
         }
@@ -668,17 +850,18 @@ pub fn detect_cheat_code_activation(
 fn show_terminal_toaster_notification(
     player_query: Query<&Transform, With<Player>>,
     mut toast_writer: EventWriter<ShowToast>,
-    keyboard_input: Res<Input<KeyCode>>,
+    actions: Res<Input<InputAction>>,
+    bindings: Res<InputBindings>,
 ) {
-    let right = keyboard_input.just_released(KeyCode::D);
-    let left = keyboard_input.just_released(KeyCode::A);
+    let right = actions.just_released(InputAction::MoveRight);
+    let left = actions.just_released(InputAction::MoveLeft);
 
     if let Some(player_transform) = player_query.iter().next() {
         if (right || left)
             && player_transform.translation.x > 1150.
             && player_transform.translation.x <= 1300.
         {
-            let value = String::from("Press E to access console");
+            let value = bindings.fill("Press {Interact} to access console");
             toast_writer.send(ShowToast {
                 value,
                 duration: Duration::from_secs(3),
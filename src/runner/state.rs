@@ -0,0 +1,138 @@
+use bevy::prelude::*;
+use bevy_kira_audio::{Audio, AudioChannel};
+use bevy_rapier2d::prelude::*;
+
+use crate::cheat_codes::{CheatCodeKind, CheatCodeResource};
+use crate::controls::InputAction;
+
+use super::jump::JumpController;
+use super::player::{Player, PlayerAnimationResource, PlayerAnimationTimer};
+
+// the player shows as hurt at the start of the invincibility, in seconds
+const HURT_DURATION: f32 = 0.3;
+const HURT_TINT: Color = Color::rgb(1.0, 0.4, 0.4);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlayerState {
+    Idle,
+    Run,
+    Jump,
+    Fall,
+    DoubleJump,
+    Dash,
+    Crouch,
+    Hurt,
+    Dead,
+}
+
+impl PlayerState {
+    /// Whether the animation of the state starts over once finished
+    pub fn is_looping(&self) -> bool {
+        matches!(
+            self,
+            PlayerState::Idle
+                | PlayerState::Run
+                | PlayerState::Crouch
+                | PlayerState::Hurt
+                | PlayerState::Dead
+        )
+    }
+}
+
+/// Sent when the player changes state, the enter and exit hooks follow it
+pub struct PlayerStateChangedEvent {
+    pub from: PlayerState,
+    pub to: PlayerState,
+}
+
+pub fn update_player_state(
+    mut player_query: Query<(&mut Player, &JumpController, &RigidBodyVelocityComponent)>,
+    mut state_events: EventWriter<PlayerStateChangedEvent>,
+    actions: Res<Input<InputAction>>,
+    cheat_codes: Res<CheatCodeResource>,
+) {
+    let wants_crouch = cheat_codes.is_code_activated(&CheatCodeKind::Crouch)
+        && actions.pressed(InputAction::Crouch);
+
+    for (mut player, jump_controller, rb_vel) in player_query.iter_mut() {
+        let is_grounded = !player.feet_touching_platforms.platforms.is_empty();
+
+        let state = if player.lives <= 0 {
+            PlayerState::Dead
+        } else if player.is_dashing {
+            PlayerState::Dash
+        } else if !player.hurt_timer.finished() && player.hurt_timer.elapsed_secs() < HURT_DURATION
+        {
+            PlayerState::Hurt
+        } else if !is_grounded {
+            if rb_vel.linvel.y <= 0.0 {
+                PlayerState::Fall
+            } else if jump_controller.is_air_jump() {
+                PlayerState::DoubleJump
+            } else {
+                PlayerState::Jump
+            }
+        } else if wants_crouch {
+            PlayerState::Crouch
+        } else if rb_vel.linvel.x != 0.0 {
+            PlayerState::Run
+        } else {
+            PlayerState::Idle
+        };
+
+        if state != player.state {
+            state_events.send(PlayerStateChangedEvent {
+                from: player.state,
+                to: state,
+            });
+            player.state = state;
+        }
+    }
+}
+
+/// Enter and exit hooks of the player states
+pub fn change_player_state(
+    mut state_events: EventReader<PlayerStateChangedEvent>,
+    mut player_animation_resource: ResMut<PlayerAnimationResource>,
+    mut sprite_query: Query<&mut TextureAtlasSprite, With<PlayerAnimationTimer>>,
+    asset_server: Res<AssetServer>,
+    audio: Res<Audio>,
+) {
+    for event in state_events.iter() {
+        for mut sprite in sprite_query.iter_mut() {
+            if event.from == PlayerState::Hurt {
+                sprite.color = Color::WHITE;
+            }
+
+            match event.to {
+                PlayerState::Jump | PlayerState::DoubleJump => {
+                    sprite.index = player_animation_resource.jump.offset;
+                    let audio_channel = AudioChannel::new("movement-channel".to_owned());
+                    audio.set_volume_in_channel(10.0, &audio_channel);
+                    audio.play_in_channel(asset_server.load("jump.ogg"), &audio_channel);
+                }
+                // walking off a ledge shows the end of the jump
+                PlayerState::Fall
+                    if !matches!(event.from, PlayerState::Jump | PlayerState::DoubleJump) =>
+                {
+                    sprite.index = player_animation_resource.jump.offset
+                        + player_animation_resource.jump.length
+                        - 1;
+                }
+                PlayerState::Dash => {
+                    sprite.index = player_animation_resource.dash_attack.offset;
+                    let audio_channel = AudioChannel::new("ability-channel".to_owned());
+                    audio.set_volume_in_channel(5.0, &audio_channel);
+                    audio.play_in_channel(asset_server.load("dash.ogg"), &audio_channel);
+                }
+                PlayerState::Run => {
+                    player_animation_resource.run_step_counter = 0;
+                }
+                PlayerState::Hurt => {
+                    sprite.color = HURT_TINT;
+                }
+                _ => {}
+            }
+        }
+    }
+}
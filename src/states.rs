@@ -10,4 +10,5 @@ pub enum GameStates {
     TabMenu,
     GameOver,
     MainMenu,
+    ChunkEditor,
 }
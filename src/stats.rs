@@ -1,17 +1,25 @@
 use bevy::prelude::*;
 use bevy_rapier2d::prelude::RigidBodyVelocityComponent;
 
-use crate::{cheat_codes::CheatCodeResource, runner::Player, states::GameStates};
+use crate::{
+    cheat_codes::CheatCodeResource,
+    enemies::EnemyKind,
+    runner::{Player, PlayerState, PlayerStateChangedEvent},
+    states::GameStates,
+};
 
 pub struct GameStatsPlugin;
 
 impl Plugin for GameStatsPlugin {
     fn build(&self, app: &mut App) {
         app.add_event::<EnemyKilledEvent>();
+        app.add_event::<BossDefeatedEvent>();
         app.insert_resource(GameStatsResource::new());
         app.add_system(enemy_killed_handler);
+        app.add_system(boss_defeated_handler);
         app.add_system(update_max_distance);
         app.add_system(update_cheats_activated);
+        app.add_system(count_player_moves);
         app.add_system_set(SystemSet::on_update(GameStates::Main).with_system(update_run_time));
         app.add_system_set(SystemSet::on_update(GameStates::Main).with_system(update_avg_speed));
     }
@@ -21,9 +29,12 @@ pub struct GameStatsResource {
     pub distance: f32,
     pub enemy_killed: usize,
     pub enemy_score: usize,
+    pub bosses_defeated: usize,
     pub cheats_activated: usize,
     pub run_time: f64,
     pub avg_speed: f32,
+    pub jumps: usize,
+    pub dashes: usize,
 }
 
 impl GameStatsResource {
@@ -32,9 +43,12 @@ impl GameStatsResource {
             distance: 0.,
             enemy_killed: 0,
             enemy_score: 0,
+            bosses_defeated: 0,
             cheats_activated: 0,
             run_time: 0.,
             avg_speed: 0.,
+            jumps: 0,
+            dashes: 0,
         }
     }
 
@@ -51,15 +65,35 @@ impl GameStatsResource {
     }
 }
 
-pub struct EnemyKilledEvent(usize);
+/// Sent when the player kills an enemy
+pub struct EnemyKilledEvent {
+    pub kind: EnemyKind,
+    // in pixels
+    pub position: Vec2,
+    pub score: usize,
+    // chunk the enemy belonged to, its drops are despawned with it
+    pub chunk: Option<Entity>,
+}
 
 pub fn enemy_killed_handler(
     mut enemy_event_reader: EventReader<EnemyKilledEvent>,
     mut stats_res: ResMut<GameStatsResource>,
 ) {
-    for EnemyKilledEvent(score) in enemy_event_reader.iter() {
+    for event in enemy_event_reader.iter() {
         stats_res.enemy_killed += 1;
-        stats_res.enemy_score += score;
+        stats_res.enemy_score += event.score;
+    }
+}
+
+/// Sent when the player defeats a boss
+pub struct BossDefeatedEvent;
+
+pub fn boss_defeated_handler(
+    mut boss_event_reader: EventReader<BossDefeatedEvent>,
+    mut stats_res: ResMut<GameStatsResource>,
+) {
+    for _ in boss_event_reader.iter() {
+        stats_res.bosses_defeated += 1;
     }
 }
 
@@ -90,6 +124,19 @@ pub fn update_cheats_activated(
         .count()
 }
 
+pub fn count_player_moves(
+    mut state_events: EventReader<PlayerStateChangedEvent>,
+    mut stats_res: ResMut<GameStatsResource>,
+) {
+    for event in state_events.iter() {
+        match event.to {
+            PlayerState::Jump | PlayerState::DoubleJump => stats_res.jumps += 1,
+            PlayerState::Dash => stats_res.dashes += 1,
+            _ => {}
+        }
+    }
+}
+
 pub fn update_run_time(time: Res<Time>, mut stats_res: ResMut<GameStatsResource>) {
     stats_res.run_time += time.delta_seconds_f64();
 }
@@ -2,6 +2,7 @@ use bevy::prelude::*;
 
 use crate::{
     cheat_codes::{CheatCodeKind, CheatCodeResource},
+    platforms::difficulty::DifficultyCurveResource,
     stats::GameStatsResource,
 };
 
@@ -16,6 +17,7 @@ pub fn build_ui(
     query: Query<Entity, With<TabMenuContent>>,
     cheat_codes_res: Res<CheatCodeResource>,
     stats_res: Res<GameStatsResource>,
+    difficulty_curve: Res<DifficultyCurveResource>,
     window: Res<Windows>,
 ) {
     let current_window = window.get_primary().unwrap();
@@ -89,13 +91,16 @@ pub fn build_ui(
         text: Text {
             sections: vec![TextSection {
                 value: format!(
-                    "Score: {}\n\nDistance: {:.2}m\n\nTime: {}\n\nAvg speed: {:.2}m/s\n\nCodes activated: {}/{}",
+                    "Score: {}\n\nDistance: {:.2}m\n\nTime: {}\n\nAvg speed: {:.2}m/s\n\nCodes activated: {}/{}\n\nJumps: {}\n\nDashes: {}\n\nThreat level: {:.1}",
                     stats_res.get_score(),
                     stats_res.distance,
                     format_time(stats_res.run_time),
                     stats_res.avg_speed,
                     stats_res.cheats_activated,
-                    cheat_codes_res.codes.len()
+                    cheat_codes_res.codes.len(),
+                    stats_res.jumps,
+                    stats_res.dashes,
+                    difficulty_curve.at(stats_res.distance).threat_level()
                 ),
                 style: TextStyle {
                     font: assets.font.clone(),
@@ -8,7 +8,9 @@ mod second_page;
 
 use crate::{
     cheat_codes::{CheatCodeKind, CheatCodeResource},
-    runner::CollectedChars,
+    controls::{InputAction, InputBindings},
+    platforms::difficulty::DifficultyCurveResource,
+    runner::{CodeFragments, CollectedChars},
     states::GameStates,
     stats::GameStatsResource,
     toast::ShowToast,
@@ -63,14 +65,16 @@ pub struct TabMenuContent;
 fn switch_page(
     commands: Commands,
     mut assets: ResMut<TabMenuAssets>,
-    mut keyboard: ResMut<Input<KeyCode>>,
+    mut actions: ResMut<Input<InputAction>>,
     query: Query<Entity, With<TabMenuContent>>,
     window: Res<Windows>,
     cheat_codes_res: Res<CheatCodeResource>,
     collected_chars_res: Res<CollectedChars>,
+    fragments_res: Res<CodeFragments>,
     stats_res: Res<GameStatsResource>,
+    difficulty_curve: Res<DifficultyCurveResource>,
 ) {
-    if keyboard.just_pressed(KeyCode::Tab) {
+    if actions.just_pressed(InputAction::Journal) {
         // on the first page
         if assets.current_page == "first".to_string() {
             assets.current_page = "second".to_string();
@@ -80,14 +84,23 @@ fn switch_page(
                 query,
                 cheat_codes_res,
                 collected_chars_res,
+                fragments_res,
                 window,
             );
         } else {
             assets.current_page = "first".to_string();
-            first_page::build_ui(commands, assets, query, cheat_codes_res, stats_res, window);
+            first_page::build_ui(
+                commands,
+                assets,
+                query,
+                cheat_codes_res,
+                stats_res,
+                difficulty_curve,
+                window,
+            );
         }
 
-        keyboard.reset(KeyCode::Tab);
+        actions.reset(InputAction::Journal);
     }
 }
 
@@ -98,29 +111,29 @@ fn destroy_menu(mut commands: Commands, query: Query<Entity, With<TabMenuCompone
 }
 
 fn open_menu_trigger(
-    mut keyboard: ResMut<Input<KeyCode>>,
+    mut actions: ResMut<Input<InputAction>>,
     mut game_state: ResMut<State<GameStates>>,
 ) {
-    if keyboard.just_pressed(KeyCode::Tab) {
+    if actions.just_pressed(InputAction::Journal) {
         game_state.push(GameStates::TabMenuLoading).unwrap();
-        keyboard.reset(KeyCode::Tab);
+        actions.reset(InputAction::Journal);
     }
 }
 
 fn close_menu_trigger(
-    mut keyboard: ResMut<Input<KeyCode>>,
+    mut actions: ResMut<Input<InputAction>>,
     mut game_state: ResMut<State<GameStates>>,
 ) {
-    if keyboard.just_pressed(KeyCode::Escape) {
+    if actions.just_pressed(InputAction::Pause) {
         game_state.pop().unwrap();
-        keyboard.reset(KeyCode::Tab);
-        keyboard.reset(KeyCode::Escape);
+        actions.reset(InputAction::Journal);
+        actions.reset(InputAction::Pause);
     }
 }
 
-fn remind_second_page(mut ev_writer: EventWriter<ShowToast>) {
+fn remind_second_page(mut ev_writer: EventWriter<ShowToast>, bindings: Res<InputBindings>) {
     ev_writer.send(ShowToast {
-        value: "Press TAB to turn page".to_string(),
+        value: bindings.fill("Press {Journal} to turn page"),
         duration: Duration::from_secs(2),
     });
 }
@@ -1,7 +1,7 @@
 use bevy::prelude::*;
 
 use crate::cheat_codes::CheatCodeResource;
-use crate::runner::{CollectedChars, LETTERS};
+use crate::runner::{CodeFragments, CollectedChars, LETTERS};
 
 use super::{TabMenuAssets, TabMenuContent};
 
@@ -14,6 +14,7 @@ pub fn build_ui(
     query: Query<Entity, With<TabMenuContent>>,
     cheat_codes_res: Res<CheatCodeResource>,
     collected_chars_res: Res<CollectedChars>,
+    fragments_res: Res<CodeFragments>,
     window: Res<Windows>,
 ) {
     let current_window = window.get_primary().unwrap();
@@ -87,7 +88,16 @@ pub fn build_ui(
         .codes
         .values()
         .map(|code| TextSection {
-            value: format!("{:?}: {}\n", code.kind, code.text.to_lowercase()),
+            // codes not activated yet only show the letters picked up during the run
+            value: if cheat_codes_res.is_code_activated(&code.kind) {
+                format!("{:?}: {}\n", code.kind, code.text.to_lowercase())
+            } else {
+                format!(
+                    "{:?}: {}\n",
+                    code.kind,
+                    fragments_res.display(&code.kind, &code.text.to_lowercase())
+                )
+            },
             style: TextStyle {
                 font: assets.font_2.clone(),
                 color: Color::rgb_u8(74, 28, 33).into(),
@@ -0,0 +1,115 @@
+use bevy::asset::AssetPlugin;
+use bevy::input::gamepad::{GamepadEventRaw, GamepadEventType};
+use bevy::input::InputPlugin;
+use bevy::prelude::*;
+
+use bevy_jam_1::controls::{ControlsPlugin, InputAction};
+use bevy_jam_1::platforms::data::{RonAsset, RonAssetLoader};
+use bevy_jam_1::toast::ShowToast;
+
+const GAMEPAD: Gamepad = Gamepad(0);
+
+/// App with the controls and a connected gamepad, without window or hardware
+fn controls_app() -> App {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .add_plugin(AssetPlugin)
+        .add_plugin(InputPlugin)
+        .add_asset::<RonAsset>()
+        .init_asset_loader::<RonAssetLoader>()
+        .add_event::<ShowToast>()
+        .add_plugin(ControlsPlugin);
+
+    send(&mut app, GamepadEventType::Connected);
+    app.update();
+    app
+}
+
+fn send(app: &mut App, event_type: GamepadEventType) {
+    app.world
+        .get_resource_mut::<Events<GamepadEventRaw>>()
+        .unwrap()
+        .send(GamepadEventRaw(GAMEPAD, event_type));
+}
+
+fn actions(app: &App) -> &Input<InputAction> {
+    app.world.get_resource::<Input<InputAction>>().unwrap()
+}
+
+#[test]
+fn button_press_triggers_action() {
+    let mut app = controls_app();
+
+    send(
+        &mut app,
+        GamepadEventType::ButtonChanged(GamepadButtonType::South, 1.0),
+    );
+    app.update();
+    assert!(actions(&app).just_pressed(InputAction::Jump));
+    assert!(actions(&app).pressed(InputAction::Jump));
+
+    // held down
+    app.update();
+    assert!(!actions(&app).just_pressed(InputAction::Jump));
+    assert!(actions(&app).pressed(InputAction::Jump));
+
+    send(
+        &mut app,
+        GamepadEventType::ButtonChanged(GamepadButtonType::South, 0.0),
+    );
+    app.update();
+    assert!(actions(&app).just_released(InputAction::Jump));
+    assert!(!actions(&app).pressed(InputAction::Jump));
+}
+
+#[test]
+fn stick_past_threshold_triggers_action() {
+    let mut app = controls_app();
+
+    send(
+        &mut app,
+        GamepadEventType::AxisChanged(GamepadAxisType::LeftStickX, -0.2),
+    );
+    app.update();
+    assert!(!actions(&app).pressed(InputAction::MoveLeft));
+
+    send(
+        &mut app,
+        GamepadEventType::AxisChanged(GamepadAxisType::LeftStickX, -1.0),
+    );
+    app.update();
+    assert!(actions(&app).just_pressed(InputAction::MoveLeft));
+    assert!(!actions(&app).pressed(InputAction::MoveRight));
+}
+
+#[test]
+fn reset_action_stays_released_until_pressed_again() {
+    let mut app = controls_app();
+
+    send(
+        &mut app,
+        GamepadEventType::ButtonChanged(GamepadButtonType::Start, 1.0),
+    );
+    app.update();
+    assert!(actions(&app).just_pressed(InputAction::Pause));
+
+    app.world
+        .get_resource_mut::<Input<InputAction>>()
+        .unwrap()
+        .reset(InputAction::Pause);
+    app.update();
+    assert!(!actions(&app).pressed(InputAction::Pause));
+    assert!(!actions(&app).just_pressed(InputAction::Pause));
+
+    send(
+        &mut app,
+        GamepadEventType::ButtonChanged(GamepadButtonType::Start, 0.0),
+    );
+    app.update();
+    send(
+        &mut app,
+        GamepadEventType::ButtonChanged(GamepadButtonType::Start, 1.0),
+    );
+    app.update();
+    assert!(actions(&app).just_pressed(InputAction::Pause));
+}